@@ -0,0 +1,354 @@
+pub mod rating;
+pub mod stats;
+
+use {
+	self::{
+		rating::{Rating, TeamRating},
+		stats::PlayerStats,
+	},
+	crate::prelude::*,
+};
+
+use {
+	actix::prelude::*,
+	rand::{distributions::Alphanumeric, Rng},
+	std::{
+		collections::HashMap,
+		fs,
+		hash::Hash,
+		path::PathBuf,
+		time::{Duration, Instant},
+	},
+};
+
+const SESSION_TOKEN_LEN: usize = 32;
+pub const SESSION_DURATION: Duration = Duration::from_secs(30 * 24 * 3600);
+const MAX_USERNAME_LEN: usize = 32;
+pub const MIN_PASSWORD_LEN: usize = 6;
+/// At most `MAX_FAILED_LOGINS` failed logins per address, and per username from an address, in any `FAILED_LOGIN_WINDOW`.
+/// Counting per username alone would let anyone lock an account out.
+const MAX_FAILED_LOGINS: usize = 5;
+const FAILED_LOGIN_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Registered accounts, saved to a JSON file on every change
+///
+/// Sessions only live in memory: players log in again after a server restart.
+pub struct Accounts {
+	path: PathBuf,
+	accounts: HashMap<String, Account>,
+	teams: Vec<TeamRating>,
+	sessions: HashMap<String, Session>,
+	/// Times of the recent failed logins, attempts counting as failed until they succeed
+	failed_logins_by_address: HashMap<String, Vec<Instant>>,
+	failed_logins_by_username: HashMap<(String, String), Vec<Instant>>,
+}
+
+/// Content of the accounts file
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct SavedAccounts {
+	accounts: Vec<Account>,
+	teams: Vec<TeamRating>,
+}
+
+/// Files written before team ratings only hold the list of accounts
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountsFile {
+	Saved(SavedAccounts),
+	AccountsOnly(Vec<Account>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Account {
+	pub username: String,
+	pub display_name: String,
+	/// PHC string (argon2)
+	pub password_hash: String,
+	#[serde(default)]
+	pub stats: PlayerStats,
+	#[serde(default)]
+	pub rating: Rating,
+}
+
+struct Session {
+	username: String,
+	expires_at: Instant,
+}
+
+/// Who an authenticated connection belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Identity {
+	pub username: String,
+	pub display_name: String,
+}
+
+impl Account {
+	fn identity(&self) -> Identity {
+		Identity {
+			username: self.username.clone(),
+			display_name: self.display_name.clone(),
+		}
+	}
+}
+
+impl Accounts {
+	pub fn load(path: PathBuf) -> crate::Result<Self> {
+		let saved: SavedAccounts = match fs::read(&path) {
+			Ok(bytes) => match serde_json::from_slice(&bytes)? {
+				AccountsFile::Saved(saved) => saved,
+				AccountsFile::AccountsOnly(accounts) => SavedAccounts {
+					accounts,
+					teams: Vec::new(),
+				},
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => SavedAccounts::default(),
+			Err(e) => return Err(e.into()),
+		};
+		info!("Loaded {} accounts from {}", saved.accounts.len(), path.display());
+		Ok(Self {
+			path,
+			accounts: saved.accounts.into_iter().map(|a| (a.username.clone(), a)).collect(),
+			teams: saved.teams,
+			sessions: HashMap::new(),
+			failed_logins_by_address: HashMap::new(),
+			failed_logins_by_username: HashMap::new(),
+		})
+	}
+
+	fn save(&self) -> crate::Result<()> {
+		let mut accounts: Vec<&Account> = self.accounts.values().collect();
+		accounts.sort_by(|a, b| a.username.cmp(&b.username));
+		// Write then rename so that a crash can't leave a truncated file
+		let tmp_path = self.path.with_extension("tmp");
+		let saved = serde_json::json!({ "accounts": accounts, "teams": self.teams });
+		fs::write(&tmp_path, serde_json::to_vec_pretty(&saved)?)?;
+		fs::rename(&tmp_path, &self.path)?;
+		Ok(())
+	}
+
+	fn new_session(&mut self, username: String) -> String {
+		let now = Instant::now();
+		self.sessions.retain(|_, s| s.expires_at > now);
+		let token: String = rand::thread_rng()
+			.sample_iter(&Alphanumeric)
+			.take(SESSION_TOKEN_LEN)
+			.map(char::from)
+			.collect();
+		self.sessions.insert(
+			token.clone(),
+			Session {
+				username,
+				expires_at: now + SESSION_DURATION,
+			},
+		);
+		token
+	}
+}
+
+/// Forgets failures that are out of the window, true if too many are left for `key`
+fn too_many_failures<K: Eq + Hash>(failures: &mut HashMap<K, Vec<Instant>>, key: &K) -> bool {
+	let now = Instant::now();
+	failures.retain(|_, times| {
+		times.retain(|&t| now.duration_since(t) < FAILED_LOGIN_WINDOW);
+		!times.is_empty()
+	});
+	failures.get(key).is_some_and(|times| times.len() >= MAX_FAILED_LOGINS)
+}
+
+impl Actor for Accounts {
+	type Context = Context<Self>;
+}
+
+/// Hashing is done by the caller, outside of the actor
+#[derive(Message)]
+#[rtype(result = "crate::Result<Identity>")]
+pub struct Register {
+	pub username: String,
+	pub display_name: String,
+	pub password_hash: String,
+}
+
+impl Handler<Register> for Accounts {
+	type Result = crate::Result<Identity>;
+
+	fn handle(&mut self, msg: Register, _ctx: &mut Context<Self>) -> Self::Result {
+		let username = msg.username.trim();
+		let display_name = msg.display_name.trim();
+		if username.is_empty() || username.chars().count() > MAX_USERNAME_LEN {
+			return Err(err_msg("Invalid username"));
+		}
+		if display_name.is_empty() || display_name.chars().count() > MAX_USERNAME_LEN {
+			return Err(err_msg("Invalid display name"));
+		}
+		if self.accounts.contains_key(username) {
+			return Err(err_msg("Username already taken"));
+		}
+		let account = Account {
+			username: username.to_owned(),
+			display_name: display_name.to_owned(),
+			password_hash: msg.password_hash,
+			stats: PlayerStats::default(),
+			rating: Rating::default(),
+		};
+		let identity = account.identity();
+		self.accounts.insert(account.username.clone(), account);
+		self.save()?;
+		Ok(identity)
+	}
+}
+
+/// Turned down after too many failed logins, for the account or from the address
+/// Starts a login attempt, returning the password hash to check against.
+/// The attempt counts as failed unless `LoginSucceeded` follows, so that parallel attempts can't outrun the limit.
+#[derive(Message)]
+#[rtype(result = "crate::Result<Option<String>>")]
+pub struct GetPasswordHash {
+	pub username: String,
+	pub address: String,
+}
+
+impl Handler<GetPasswordHash> for Accounts {
+	type Result = crate::Result<Option<String>>;
+
+	fn handle(&mut self, msg: GetPasswordHash, _ctx: &mut Context<Self>) -> Self::Result {
+		let username_key = (msg.username, msg.address);
+		if too_many_failures(&mut self.failed_logins_by_address, &username_key.1)
+			|| too_many_failures(&mut self.failed_logins_by_username, &username_key)
+		{
+			return Err(err_msg("Too many failed logins, try again later"));
+		}
+		let now = Instant::now();
+		self.failed_logins_by_address
+			.entry(username_key.1.clone())
+			.or_default()
+			.push(now);
+		let password_hash = self.accounts.get(&username_key.0).map(|a| a.password_hash.clone());
+		self.failed_logins_by_username
+			.entry(username_key)
+			.or_default()
+			.push(now);
+		Ok(password_hash)
+	}
+}
+
+/// The password matched: the attempt started by `GetPasswordHash` doesn't count as a failure
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct LoginSucceeded {
+	pub username: String,
+	pub address: String,
+}
+
+impl Handler<LoginSucceeded> for Accounts {
+	type Result = ();
+
+	fn handle(&mut self, msg: LoginSucceeded, _ctx: &mut Context<Self>) {
+		if let Some(times) = self.failed_logins_by_address.get_mut(&msg.address) {
+			times.pop();
+		}
+		if let Some(times) = self.failed_logins_by_username.get_mut(&(msg.username, msg.address)) {
+			times.pop();
+		}
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "bool")]
+pub struct IsRegistered {
+	pub username: String,
+}
+
+impl Handler<IsRegistered> for Accounts {
+	type Result = bool;
+
+	fn handle(&mut self, msg: IsRegistered, _ctx: &mut Context<Self>) -> Self::Result {
+		self.accounts.contains_key(&msg.username)
+	}
+}
+
+/// Sent once the password was checked, returns the session token
+#[derive(Message)]
+#[rtype(result = "crate::Result<(String, Identity)>")]
+pub struct OpenSession {
+	pub username: String,
+}
+
+impl Handler<OpenSession> for Accounts {
+	type Result = crate::Result<(String, Identity)>;
+
+	fn handle(&mut self, msg: OpenSession, _ctx: &mut Context<Self>) -> Self::Result {
+		let identity = self
+			.accounts
+			.get(&msg.username)
+			.ok_or_else(|| err_msg("Unknown account"))?
+			.identity();
+		Ok((self.new_session(msg.username), identity))
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CloseSession {
+	pub token: String,
+}
+
+impl Handler<CloseSession> for Accounts {
+	type Result = ();
+
+	fn handle(&mut self, msg: CloseSession, _ctx: &mut Context<Self>) {
+		self.sessions.remove(&msg.token);
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<Identity>")]
+pub struct Authenticate {
+	pub token: String,
+}
+
+impl Handler<Authenticate> for Accounts {
+	type Result = Option<Identity>;
+
+	fn handle(&mut self, msg: Authenticate, _ctx: &mut Context<Self>) -> Self::Result {
+		let session = self.sessions.get(&msg.token)?;
+		if session.expires_at <= Instant::now() {
+			self.sessions.remove(&msg.token);
+			return None;
+		}
+		self.accounts.get(&session.username).map(Account::identity)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	async fn attempt(accounts: &Addr<Accounts>, username: &str, address: &str) -> bool {
+		let get_hash = GetPasswordHash {
+			username: username.to_owned(),
+			address: address.to_owned(),
+		};
+		accounts.send(get_hash).await.unwrap().is_ok()
+	}
+
+	#[actix_web::test]
+	async fn failed_logins_are_throttled() {
+		let path = std::env::temp_dir().join(format!("coinche-login-test-{}.json", std::process::id()));
+		let accounts = Accounts::load(path).unwrap().start();
+		for _ in 0..MAX_FAILED_LOGINS {
+			assert!(attempt(&accounts, "bob", "1.1.1.1").await);
+		}
+		assert!(!attempt(&accounts, "bob", "1.1.1.1").await);
+		// Others can still log into the account
+		assert!(attempt(&accounts, "bob", "2.2.2.2").await);
+		for _ in 0..MAX_FAILED_LOGINS {
+			assert!(attempt(&accounts, "alice", "3.3.3.3").await);
+			accounts.do_send(LoginSucceeded {
+				username: "alice".to_owned(),
+				address: "3.3.3.3".to_owned(),
+			});
+		}
+		assert!(attempt(&accounts, "alice", "3.3.3.3").await);
+	}
+}
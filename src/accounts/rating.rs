@@ -0,0 +1,254 @@
+use super::Accounts;
+
+use actix::prelude::*;
+
+pub const INITIAL_RATING: f64 = 1500.;
+/// Maximum rating change for a single match
+const K_FACTOR: f64 = 32.;
+/// Entries returned by the leaderboard, out of the accounts and teams which played at least one match
+const LEADERBOARD_LEN: usize = 100;
+/// Rating changes kept, as all of them are saved with the accounts
+const HISTORY_LEN: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rating {
+	pub rating: f64,
+	pub matches_played: usize,
+	/// Rating after each of the last `HISTORY_LEN` matches, oldest first
+	pub history: Vec<RatingChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingChange {
+	/// Unix timestamp (ms)
+	pub timestamp: i64,
+	pub rating: f64,
+}
+
+/// Rating of a pair of registered players, as a team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamRating {
+	/// Usernames, sorted
+	pub players: [String; 2],
+	#[serde(flatten)]
+	pub rating: Rating,
+}
+
+impl Default for Rating {
+	fn default() -> Self {
+		Self {
+			rating: INITIAL_RATING,
+			matches_played: 0,
+			history: Vec::new(),
+		}
+	}
+}
+
+impl Rating {
+	fn update(&mut self, delta: f64, timestamp: i64) {
+		self.rating += delta;
+		self.matches_played += 1;
+		if self.history.len() >= HISTORY_LEN {
+			self.history.drain(..=self.history.len() - HISTORY_LEN);
+		}
+		self.history.push(RatingChange {
+			timestamp,
+			rating: self.rating,
+		});
+	}
+}
+
+/// Probability for a team rated `rating` to beat one rated `opponent_rating`
+fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+	1. / (1. + 10f64.powf((opponent_rating - rating) / 400.))
+}
+
+fn team_key(a: &str, b: &str) -> [String; 2] {
+	match a <= b {
+		true => [a.to_owned(), b.to_owned()],
+		false => [b.to_owned(), a.to_owned()],
+	}
+}
+
+impl Accounts {
+	fn player_rating(&self, account: Option<&String>) -> f64 {
+		account
+			.and_then(|a| self.accounts.get(a))
+			.map_or(INITIAL_RATING, |a| a.rating.rating)
+	}
+
+	/// Partners' average: a team is as strong as its players together
+	fn players_team_rating(&self, partners: [Option<&String>; 2]) -> f64 {
+		(self.player_rating(partners[0]) + self.player_rating(partners[1])) / 2.
+	}
+
+	fn team_rating(&self, partners: [Option<&String>; 2]) -> Option<&TeamRating> {
+		let key = team_key(partners[0]?, partners[1]?);
+		self.teams.iter().find(|t| t.players == key)
+	}
+
+	/// Updates the individual and team ratings of registered players after a match,
+	/// `seats` holding the account of the player of each seat, if any
+	pub fn rate_match(&mut self, seats: &[Option<String>; 4], winning_team: bool) {
+		let timestamp = chrono::Utc::now().timestamp_millis();
+		let partners = |team: bool| [seats[team as usize].as_ref(), seats[team as usize + 2].as_ref()];
+		let players_ratings = [false, true].map(|team| self.players_team_rating(partners(team)));
+		// Pairs which never played together start from their players' average
+		let team_ratings = [false, true].map(|team| {
+			self.team_rating(partners(team))
+				.map_or(players_ratings[team as usize], |t| t.rating.rating)
+		});
+		for team in [false, true] {
+			let score = (team == winning_team) as u8 as f64;
+			let player_delta =
+				K_FACTOR * (score - expected_score(players_ratings[team as usize], players_ratings[!team as usize]));
+			for account in partners(team).into_iter().flatten() {
+				if let Some(account) = self.accounts.get_mut(account) {
+					account.rating.update(player_delta, timestamp);
+				}
+			}
+			if let [Some(a), Some(b)] = partners(team) {
+				let key = team_key(a, b);
+				let team_delta =
+					K_FACTOR * (score - expected_score(team_ratings[team as usize], team_ratings[!team as usize]));
+				let team_rating = match self.teams.iter().position(|t| t.players == key) {
+					Some(pos) => &mut self.teams[pos],
+					None => {
+						self.teams.push(TeamRating {
+							players: key,
+							rating: Rating {
+								rating: team_ratings[team as usize],
+								..Rating::default()
+							},
+						});
+						self.teams.last_mut().unwrap()
+					}
+				};
+				team_rating.rating.update(team_delta, timestamp);
+			}
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct Leaderboard {
+	pub players: Vec<PlayerRanking>,
+	pub teams: Vec<TeamRating>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayerRanking {
+	pub username: String,
+	pub display_name: String,
+	#[serde(flatten)]
+	pub rating: Rating,
+}
+
+#[derive(Message)]
+#[rtype(result = "Leaderboard")]
+pub struct GetLeaderboard;
+
+impl Handler<GetLeaderboard> for Accounts {
+	type Result = MessageResult<GetLeaderboard>;
+
+	fn handle(&mut self, _msg: GetLeaderboard, _ctx: &mut Context<Self>) -> Self::Result {
+		let mut players: Vec<PlayerRanking> = self
+			.accounts
+			.values()
+			.filter(|a| a.rating.matches_played > 0)
+			.map(|a| PlayerRanking {
+				username: a.username.clone(),
+				display_name: a.display_name.clone(),
+				rating: a.rating.clone(),
+			})
+			.collect();
+		players.sort_by(|a, b| b.rating.rating.total_cmp(&a.rating.rating));
+		players.truncate(LEADERBOARD_LEN);
+		let mut teams = self.teams.clone();
+		teams.sort_by(|a, b| b.rating.rating.total_cmp(&a.rating.rating));
+		teams.truncate(LEADERBOARD_LEN);
+		MessageResult(Leaderboard { players, teams })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::*,
+		crate::accounts::{stats::PlayerStats, Account},
+	};
+
+	fn accounts(usernames: &[&str]) -> Accounts {
+		let mut accounts = Accounts::load(std::env::temp_dir().join("coinche-rating-test-missing.json")).unwrap();
+		for &username in usernames {
+			let account = Account {
+				username: username.to_owned(),
+				display_name: username.to_owned(),
+				password_hash: String::new(),
+				stats: PlayerStats::default(),
+				rating: Rating::default(),
+			};
+			accounts.accounts.insert(username.to_owned(), account);
+		}
+		accounts
+	}
+
+	fn seats(usernames: [Option<&str>; 4]) -> [Option<String>; 4] {
+		usernames.map(|u| u.map(str::to_owned))
+	}
+
+	fn rating(accounts: &Accounts, username: &str) -> f64 {
+		accounts.accounts[username].rating.rating
+	}
+
+	#[test]
+	fn even_teams_move_by_half_the_k_factor() {
+		let mut accounts = accounts(&["a", "b", "c", "d"]);
+		accounts.rate_match(&seats([Some("a"), Some("b"), Some("c"), Some("d")]), true);
+		for winner in ["b", "d"] {
+			assert_eq!(rating(&accounts, winner), INITIAL_RATING + K_FACTOR / 2.);
+		}
+		for loser in ["a", "c"] {
+			assert_eq!(rating(&accounts, loser), INITIAL_RATING - K_FACTOR / 2.);
+		}
+		assert_eq!(accounts.accounts["a"].rating.matches_played, 1);
+		assert_eq!(accounts.accounts["a"].rating.history.len(), 1);
+	}
+
+	#[test]
+	fn pairs_of_registered_players_get_a_team_rating() {
+		let mut accounts = accounts(&["a", "b", "c"]);
+		accounts.rate_match(&seats([Some("c"), Some("b"), Some("a"), None]), false);
+		// Guests aren't rated, and neither are the teams they play in
+		assert_eq!(accounts.teams.len(), 1);
+		let team = &accounts.teams[0];
+		assert_eq!(team.players, ["a".to_owned(), "c".to_owned()]);
+		assert_eq!(team.rating.rating, INITIAL_RATING + K_FACTOR / 2.);
+		assert_eq!(rating(&accounts, "b"), INITIAL_RATING - K_FACTOR / 2.);
+	}
+
+	#[test]
+	fn only_recent_changes_are_kept() {
+		let mut accounts = accounts(&["a", "b", "c", "d"]);
+		for winning_team in (0..HISTORY_LEN + 10).map(|i| i % 2 == 0) {
+			accounts.rate_match(&seats([Some("a"), Some("b"), Some("c"), Some("d")]), winning_team);
+		}
+		let rating = &accounts.accounts["a"].rating;
+		assert_eq!(rating.matches_played, HISTORY_LEN + 10);
+		assert_eq!(rating.history.len(), HISTORY_LEN);
+		assert_eq!(rating.history.last().unwrap().rating, rating.rating);
+		assert_eq!(accounts.teams[0].rating.history.len(), HISTORY_LEN);
+	}
+
+	#[test]
+	fn upsets_move_ratings_more() {
+		let mut accounts = accounts(&["a", "b", "c", "d"]);
+		for _ in 0..3 {
+			accounts.rate_match(&seats([Some("a"), Some("b"), Some("c"), Some("d")]), false);
+		}
+		let favourite = rating(&accounts, "a");
+		accounts.rate_match(&seats([Some("a"), Some("b"), Some("c"), Some("d")]), true);
+		let loss = favourite - rating(&accounts, "a");
+		assert!(loss > K_FACTOR / 2. && loss < K_FACTOR, "{loss}");
+	}
+}
@@ -0,0 +1,165 @@
+use {super::Accounts, crate::prelude::*};
+
+use actix::prelude::*;
+
+/// Kept along with the account
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStats {
+	pub deals_played: usize,
+	/// Sum of the points scored by the player's team over all deals
+	pub points: usize,
+	/// Contracts won at bidding by the player
+	pub contracts: Vec<ContractStats>,
+	pub coinches: Outcomes,
+	pub surcoinches: Outcomes,
+	pub belotes: usize,
+	/// Deals where the player's team took every trick
+	pub capots: usize,
+	pub matches_played: usize,
+	pub matches_won: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractStats {
+	pub score: BidScore,
+	pub trump: Trump,
+	pub taken: usize,
+	pub made: usize,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Outcomes {
+	pub won: usize,
+	pub lost: usize,
+}
+
+impl Outcomes {
+	fn record(&mut self, won: bool) {
+		match won {
+			true => self.won += 1,
+			false => self.lost += 1,
+		}
+	}
+}
+
+/// What one account did during a deal
+#[derive(Debug)]
+pub struct DealRecord {
+	pub account: String,
+	pub points: usize,
+	/// The bid, and whether it was made, if the player took the contract
+	pub contract: Option<(Bid, bool)>,
+	/// Whether the player's coinche was won
+	pub coinche: Option<bool>,
+	pub surcoinche: Option<bool>,
+	pub belote: bool,
+	pub capot: bool,
+}
+
+impl PlayerStats {
+	fn record_deal(&mut self, record: &DealRecord) {
+		self.deals_played += 1;
+		self.points += record.points;
+		if let Some((bid, made)) = record.contract {
+			let contract = match self
+				.contracts
+				.iter_mut()
+				.position(|c| c.score == bid.score && c.trump == bid.trump)
+			{
+				Some(pos) => &mut self.contracts[pos],
+				None => {
+					self.contracts.push(ContractStats {
+						score: bid.score,
+						trump: bid.trump,
+						taken: 0,
+						made: 0,
+					});
+					self.contracts.last_mut().unwrap()
+				}
+			};
+			contract.taken += 1;
+			contract.made += made as usize;
+		}
+		if let Some(won) = record.coinche {
+			self.coinches.record(won);
+		}
+		if let Some(won) = record.surcoinche {
+			self.surcoinches.record(won);
+		}
+		self.belotes += record.belote as usize;
+		self.capots += record.capot as usize;
+	}
+
+	pub fn average_points_per_deal(&self) -> f64 {
+		match self.deals_played {
+			0 => 0.,
+			n => self.points as f64 / n as f64,
+		}
+	}
+
+	pub fn match_win_rate(&self) -> Option<f64> {
+		match self.matches_played {
+			0 => None,
+			n => Some(self.matches_won as f64 / n as f64),
+		}
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RecordDeal(pub Vec<DealRecord>);
+
+impl Handler<RecordDeal> for Accounts {
+	type Result = ();
+
+	fn handle(&mut self, msg: RecordDeal, _ctx: &mut Context<Self>) {
+		for record in msg.0.iter() {
+			if let Some(account) = self.accounts.get_mut(&record.account) {
+				account.stats.record_deal(record);
+			}
+		}
+		if let Err(err) = self.save() {
+			error!("Failed to save stats: {}", err);
+		}
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RecordMatch {
+	/// Account of the player of each seat, if logged in
+	pub seats: [Option<String>; 4],
+	pub winning_team: bool,
+}
+
+impl Handler<RecordMatch> for Accounts {
+	type Result = ();
+
+	fn handle(&mut self, msg: RecordMatch, _ctx: &mut Context<Self>) {
+		for (player_id, account) in msg.seats.iter().enumerate() {
+			if let Some(account) = account.as_ref().and_then(|a| self.accounts.get_mut(a)) {
+				account.stats.matches_played += 1;
+				account.stats.matches_won += (Player::team(player_id) == msg.winning_team) as usize;
+			}
+		}
+		self.rate_match(&msg.seats, msg.winning_team);
+		if let Err(err) = self.save() {
+			error!("Failed to save stats: {}", err);
+		}
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<PlayerStats>")]
+pub struct GetStats {
+	pub username: String,
+}
+
+impl Handler<GetStats> for Accounts {
+	type Result = Option<PlayerStats>;
+
+	fn handle(&mut self, msg: GetStats, _ctx: &mut Context<Self>) -> Self::Result {
+		self.accounts.get(&msg.username).map(|a| a.stats.clone())
+	}
+}
@@ -0,0 +1,555 @@
+use {
+	super::connection::{Connection, NetEvent},
+	coinche::{
+		game::{
+			cards::{Card, Suit},
+			chat::ChatMessage,
+			contract::{Bid, BidScore, PlayerBid, Trump},
+			turn::Turn,
+			view::Action,
+			BeloteRebelote, Board, CoincheState, Trick,
+		},
+		messages::{ClientMessage, PROTOCOL_VERSION},
+		player::{Control, Player, PlayerCardIdentifier},
+	},
+	ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+	serde_derive::Deserialize,
+	std::time::{Duration, Instant},
+};
+
+/// Lines of the log that are kept
+const LOG_LEN: usize = 500;
+/// Wait before connecting again after losing the connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+const HELP: &[&str] = &[
+	"Bid: score and trump, e.g. \"80 h\", \"capot s\" (A: no trump, T: all trump), or \"pass\"",
+	"Play: pick a card with ←/→ then Enter, or type it, e.g. \"Jh\"",
+	"Also: coinche, surcoinche, claim, yes/no, undo, redeal, pause, resume, leave",
+	"Chat: \"say <text>\", quit: \"quit\" or Ctrl-C",
+];
+
+/// Owned counterpart of the server's `TableView`, with what gets shown
+#[derive(Debug, Deserialize)]
+pub struct Table {
+	pub player_id: usize,
+	pub players: Vec<Seat>,
+	pub dealer_id: usize,
+	pub points: [usize; 2],
+	pub phase: Phase,
+	pub hand: Vec<Card>,
+	pub turn: Option<Turn>,
+	pub legal_actions: Vec<Action>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Seat {
+	pub username: String,
+	pub control: Control,
+	pub card_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum Phase {
+	Lobby,
+	Bidding {
+		bids: Vec<PlayerBid>,
+	},
+	Playing {
+		bidder_id: usize,
+		bid: Bid,
+		coinche_state: CoincheState,
+		board: Board,
+		last_trick: Option<Trick>,
+		tricks: [usize; 2],
+	},
+}
+
+/// The `ServerMessage`s that are shown in the log, other ones only trigger a refresh
+#[derive(Debug, Deserialize)]
+enum Incoming {
+	Welcome {
+		protocol_version: u32,
+		seat_token: String,
+	},
+	Table(Box<Table>),
+	PlayerBid(PlayerBid),
+	Coinche {
+		player_id: usize,
+	},
+	SurCoinche {
+		player_id: usize,
+	},
+	PlayedCard {
+		player_id: usize,
+		card: Card,
+		belote_rebelote: Option<BeloteRebelote>,
+	},
+	Trick {
+		winner_id: usize,
+	},
+	Chat(ChatMessage),
+	ChatHistory {
+		messages: Vec<ChatMessage>,
+	},
+	MatchEnd {
+		points: [usize; 2],
+		winning_team: Option<bool>,
+	},
+	Ack {},
+	Error {
+		message: String,
+	},
+}
+
+pub struct App {
+	connection: Connection,
+	url: String,
+	pub username: String,
+	password: Option<String>,
+	/// Given in `Welcome`, gets the seat back after losing the connection
+	seat_token: Option<String>,
+	/// `seq` of the last message received, so that only the missed ones are sent again after reconnecting
+	last_seq: Option<u64>,
+	/// Set while disconnected
+	reconnect_at: Option<Instant>,
+	pub table: Option<Table>,
+	pub log: Vec<String>,
+	/// Command being typed
+	pub input: String,
+	/// Position in the hand of the card to play on Enter
+	pub selected: usize,
+	/// Waiting for a `Table` after an event
+	refresh_requested: bool,
+	chat_loaded: bool,
+	pub closed: bool,
+	pub quit: bool,
+}
+
+impl App {
+	pub fn new(connection: Connection, url: String, username: String, password: Option<String>) -> Self {
+		let mut app = Self {
+			connection,
+			url,
+			username,
+			password,
+			seat_token: None,
+			last_seq: None,
+			reconnect_at: None,
+			table: None,
+			log: HELP.iter().map(|&line| line.to_owned()).collect(),
+			input: String::new(),
+			selected: 0,
+			refresh_requested: false,
+			chat_loaded: false,
+			closed: false,
+			quit: false,
+		};
+		app.init();
+		app
+	}
+
+	fn init(&mut self) {
+		self.send(&ClientMessage::Init {
+			username: self.username.clone(),
+			password: self.password.clone(),
+			protocol_version: PROTOCOL_VERSION,
+			last_seq: self.last_seq,
+			seat_token: self.seat_token.clone(),
+		});
+	}
+
+	fn send(&mut self, message: &ClientMessage) {
+		self.connection.send(serde_json::to_string(message).unwrap());
+	}
+
+	fn log(&mut self, line: String) {
+		if self.log.len() >= LOG_LEN {
+			self.log.remove(0);
+		}
+		self.log.push(line);
+	}
+
+	pub fn username_of(&self, player_id: usize) -> String {
+		self.table
+			.as_ref()
+			.and_then(|table| table.players.get(player_id))
+			.map_or_else(|| format!("Player {}", player_id), |seat| seat.username.clone())
+	}
+
+	/// Cards that may be played right now, if it's our turn to play
+	pub fn playable_cards(&self) -> Option<&[Card]> {
+		self.table
+			.as_ref()?
+			.legal_actions
+			.iter()
+			.find_map(|action| match action {
+				Action::PlayCard { cards } => Some(cards.as_slice()),
+				_ => None,
+			})
+	}
+
+	fn can(&self, matches: impl Fn(&Action) -> bool) -> bool {
+		self.table
+			.as_ref()
+			.is_some_and(|table| table.legal_actions.iter().any(matches))
+	}
+
+	pub fn poll_connection(&mut self) {
+		if self.reconnect_at.is_some_and(|at| at <= Instant::now()) {
+			match Connection::open(&self.url) {
+				Ok(connection) => {
+					self.connection = connection;
+					self.reconnect_at = None;
+					self.closed = false;
+					self.refresh_requested = false;
+					self.log("Reconnected".to_owned());
+					self.init();
+				}
+				Err(_) => self.reconnect_at = Some(Instant::now() + RECONNECT_DELAY),
+			}
+		}
+		while let Ok(event) = self.connection.events.try_recv() {
+			match event {
+				NetEvent::Message(json) => self.on_message(&json),
+				NetEvent::Closed(reason) => {
+					self.log(format!("{}, reconnecting (Ctrl-C to quit)", reason));
+					self.closed = true;
+					self.reconnect_at = Some(Instant::now() + RECONNECT_DELAY);
+				}
+			}
+		}
+	}
+
+	fn on_message(&mut self, json: &str) {
+		let (seq, message) = match parse_message(json) {
+			Some(parsed) => parsed,
+			None => return,
+		};
+		if seq.is_some() {
+			self.last_seq = seq;
+		}
+		let message = match message {
+			Ok(message) => message,
+			Err(_) => {
+				self.request_refresh();
+				return;
+			}
+		};
+		match message {
+			Incoming::Welcome {
+				protocol_version,
+				seat_token,
+			} => {
+				self.seat_token = Some(seat_token);
+				self.log(format!("Connected, protocol version {}", protocol_version))
+			}
+			Incoming::Table(table) => {
+				self.refresh_requested = false;
+				self.selected = self.selected.min(table.hand.len().saturating_sub(1));
+				self.table = Some(*table);
+			}
+			Incoming::Chat(chat) => self.log(format!("<{}> {}", chat.username, chat.text)),
+			// Sent again along with each refresh
+			Incoming::ChatHistory { messages } => {
+				if !self.chat_loaded {
+					self.chat_loaded = true;
+					for chat in messages {
+						self.log(format!("<{}> {}", chat.username, chat.text));
+					}
+				}
+			}
+			Incoming::Ack {} => (),
+			Incoming::Error { message } => self.log(format!("Error: {}", message)),
+			Incoming::PlayerBid(PlayerBid { player_id, bid }) => self.on_event(match bid {
+				Some(bid) => format!("{} bids {}", self.username_of(player_id), bid_name(bid)),
+				None => format!("{} passes", self.username_of(player_id)),
+			}),
+			Incoming::Coinche { player_id } => self.on_event(format!("{} coinches!", self.username_of(player_id))),
+			Incoming::SurCoinche { player_id } => {
+				self.on_event(format!("{} surcoinches!", self.username_of(player_id)))
+			}
+			Incoming::PlayedCard {
+				player_id,
+				card,
+				belote_rebelote,
+			} => self.on_event(match belote_rebelote {
+				Some(announce) => format!("{} plays {} ({:?})", self.username_of(player_id), card, announce),
+				None => format!("{} plays {}", self.username_of(player_id), card),
+			}),
+			Incoming::Trick { winner_id } => self.on_event(format!("{} takes the trick", self.username_of(winner_id))),
+			Incoming::MatchEnd { points, winning_team } => {
+				let result = match (winning_team, &self.table) {
+					(None, _) => "Tie",
+					(Some(team), Some(table)) if team == Player::team(table.player_id) => "We win",
+					(Some(_), _) => "We lose",
+				};
+				self.on_event(format!("Match over: {} ({} - {})", result, points[0], points[1]))
+			}
+		}
+	}
+
+	fn on_event(&mut self, line: String) {
+		self.log(line);
+		self.request_refresh();
+	}
+
+	/// Events are applied by fetching the whole view again
+	fn request_refresh(&mut self) {
+		if !self.refresh_requested {
+			self.refresh_requested = true;
+			self.send(&ClientMessage::RefreshGameState);
+		}
+	}
+
+	pub fn on_key(&mut self, key: KeyEvent) {
+		match key.code {
+			KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+			KeyCode::Char(c) => self.input.push(c),
+			KeyCode::Backspace => {
+				self.input.pop();
+			}
+			KeyCode::Esc => self.input.clear(),
+			KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+			KeyCode::Right => {
+				let hand_len = self.table.as_ref().map_or(0, |table| table.hand.len());
+				self.selected = (self.selected + 1).min(hand_len.saturating_sub(1));
+			}
+			KeyCode::Enter => {
+				let command = std::mem::take(&mut self.input);
+				match command.trim() {
+					"" => self.play_selected(),
+					command => self.run(command),
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn play_selected(&mut self) {
+		if let Some(&card) = self.table.as_ref().and_then(|table| table.hand.get(self.selected)) {
+			self.send(&ClientMessage::PlayCard(PlayerCardIdentifier::Card(card)));
+		}
+	}
+
+	fn run(&mut self, command: &str) {
+		if self.closed && command != "quit" {
+			return self.log("Not connected anymore".to_owned());
+		}
+		let lowercase = command.to_lowercase();
+		let message = match lowercase.as_str() {
+			"quit" => {
+				self.quit = true;
+				return;
+			}
+			"help" => {
+				for line in HELP {
+					self.log((*line).to_owned());
+				}
+				return;
+			}
+			"pass" if self.can(|a| matches!(a, Action::SurCoinche)) => ClientMessage::SurCoinche(false),
+			"pass" => ClientMessage::Bid(None),
+			"coinche" => ClientMessage::Coinche,
+			"surcoinche" => ClientMessage::SurCoinche(true),
+			"claim" => ClientMessage::Claim,
+			"yes" | "no" if self.can(|a| matches!(a, Action::AnswerUndo)) => {
+				ClientMessage::AnswerUndo(lowercase == "yes")
+			}
+			"yes" | "no" => ClientMessage::AnswerClaim(lowercase == "yes"),
+			"undo" => ClientMessage::RequestUndo,
+			"redeal" => ClientMessage::RequestRedeal,
+			"pause" => ClientMessage::Pause,
+			"resume" => ClientMessage::Resume,
+			"leave" => ClientMessage::Leave,
+			_ => match (
+				command.strip_prefix("say "),
+				command.parse::<Card>(),
+				parse_bid(&lowercase),
+			) {
+				(Some(text), _, _) => ClientMessage::Chat(text.to_owned()),
+				(None, Ok(card), _) => ClientMessage::PlayCard(PlayerCardIdentifier::Card(card)),
+				(None, Err(_), Some(bid)) => ClientMessage::Bid(Some(bid)),
+				(None, Err(_), None) => {
+					return self.log(format!("Unknown command \"{}\", type \"help\" for the list", command));
+				}
+			},
+		};
+		self.send(&message);
+	}
+}
+
+/// `seq` of the message if any, and the message itself, `Err` if it isn't one that gets shown
+fn parse_message(json: &str) -> Option<(Option<u64>, serde_json::Result<Incoming>)> {
+	let mut value: serde_json::Value = serde_json::from_str(json).ok()?;
+	let seq = value
+		.as_object_mut()
+		.and_then(|object| object.remove("seq"))
+		.and_then(|seq| seq.as_u64());
+	Some((seq, serde_json::from_value(value)))
+}
+
+/// Score then trump, e.g. "80 h" or "capot a"
+fn parse_bid(command: &str) -> Option<Bid> {
+	let (score, trump) = command.split_once(' ')?;
+	let score = match score {
+		"capot" => BidScore::Capot,
+		// Same as their serialized names
+		score => serde_json::from_value(serde_json::Value::String(score.to_owned())).ok()?,
+	};
+	let mut trump = trump.trim().chars();
+	let trump = match (trump.next()?, trump.next()) {
+		('a', None) => Trump::NoTrump,
+		('t', None) => Trump::AllTrump,
+		(suit, None) => Trump::Suit(Suit::from_char(suit)?),
+		_ => return None,
+	};
+	Some(Bid { trump, score })
+}
+
+pub fn bid_name(bid: Bid) -> String {
+	let score = match bid.score {
+		BidScore::Capot => "Capot".to_owned(),
+		score => score.required_points().to_string(),
+	};
+	let trump = match bid.trump {
+		Trump::NoTrump => "no trump".to_owned(),
+		Trump::AllTrump => "all trump".to_owned(),
+		Trump::Suit(suit) => format!("{:?}", suit),
+	};
+	format!("{} {}", score, trump)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::*,
+		actix::Actor,
+		coinche::{
+			accounts::Accounts,
+			game::{settings::Settings, Game},
+			messages::{EncodedMessage, Encoding, ServerMessage, PROTOCOL_FEATURES},
+		},
+	};
+
+	/// Messages the server sent to seat 0, parsed as the client does
+	fn received(game: &Game) -> Vec<(String, serde_json::Result<Incoming>)> {
+		let outbox = game.players[0].outbox.borrow();
+		outbox
+			.since(0)
+			.unwrap()
+			.map(|encoded| match encoded {
+				EncodedMessage::Text(json) => {
+					let value: serde_json::Value = serde_json::from_str(json).unwrap();
+					let name = value
+						.as_object()
+						.unwrap()
+						.keys()
+						.find(|&key| key != "seq")
+						.unwrap()
+						.clone();
+					let (seq, message) = parse_message(json).unwrap();
+					assert!(seq.is_some());
+					(name, message)
+				}
+				EncodedMessage::Binary(_) => unreachable!(),
+			})
+			.collect()
+	}
+
+	/// The client's own copies of the protocol types must keep up with the server's
+	#[actix_web::test]
+	async fn server_messages_parse_as_incoming() {
+		let accounts_path = std::env::temp_dir().join(format!("coinche-tui-test-{}.json", std::process::id()));
+		let accounts = Accounts::load(accounts_path).unwrap().start();
+		let mut game = Game::new(Settings::default(), accounts);
+		for username in ["a", "b", "c", "d"] {
+			game.players
+				.push(Player::new(username.to_owned(), None, None, Encoding::Json));
+		}
+		assert!(game.try_bidding_phase());
+		game.player(0).send_refresh_all().unwrap();
+		let bid = Bid {
+			trump: Trump::Suit(Suit::Hearts),
+			score: BidScore::_80,
+		};
+		game.player_mut(0).bid(Some(bid)).unwrap();
+		game.player_mut(1).coincher().unwrap();
+		game.player_mut(0).surcoincher(false).unwrap();
+		game.player_mut(2).surcoincher(false).unwrap();
+		game.player(0).send_refresh_all().unwrap();
+		game.player_mut(0).play_card(PlayerCardIdentifier::CardPos(0)).unwrap();
+		game.chat(0, "Bonjour".to_owned()).unwrap();
+		game.player(0).send_refresh_all().unwrap();
+
+		let shown = [
+			"Table",
+			"PlayerBid",
+			"Coinche",
+			"SurCoinche",
+			"PlayedCard",
+			"Trick",
+			"Chat",
+			"ChatHistory",
+			"MatchEnd",
+			"Ack",
+			"Error",
+		];
+		let received = received(&game);
+		for (name, message) in received.iter() {
+			if shown.contains(&name.as_str()) {
+				assert!(message.is_ok(), "{} doesn't parse: {:?}", name, message);
+			}
+		}
+		let names: Vec<&str> = received.iter().map(|(name, _)| name.as_str()).collect();
+		for name in ["Table", "PlayerBid", "Coinche", "PlayedCard", "Chat", "ChatHistory"] {
+			assert!(names.contains(&name), "{} wasn't sent: {:?}", name, names);
+		}
+		let tables: Vec<&Table> = received
+			.iter()
+			.filter_map(|(_, message)| match message {
+				Ok(Incoming::Table(table)) => Some(&**table),
+				_ => None,
+			})
+			.collect();
+		assert!(matches!(tables[0].phase, Phase::Bidding { .. }));
+		assert_eq!(tables[0].hand.len(), 8);
+		assert_eq!(tables[0].players.len(), 4);
+		assert_eq!(tables[0].legal_actions[0], Action::Bid { above: None });
+		match &tables.last().unwrap().phase {
+			Phase::Playing { bidder_id, board, .. } => {
+				assert_eq!(*bidder_id, 0);
+				assert_eq!(board.cards.len(), 1);
+			}
+			phase => panic!("Unexpected phase {:?}", phase),
+		}
+		assert_eq!(tables.last().unwrap().hand.len(), 7);
+
+		let welcome = ServerMessage::Welcome {
+			protocol_version: PROTOCOL_VERSION,
+			features: PROTOCOL_FEATURES,
+			seat_token: "token",
+		};
+		match parse_message(&serde_json::to_string(&welcome).unwrap()) {
+			Some((None, Ok(Incoming::Welcome { seat_token, .. }))) => assert_eq!(seat_token, "token"),
+			parsed => panic!("Unexpected {:?}", parsed),
+		}
+	}
+
+	#[test]
+	fn bids_parse_as_score_then_trump() {
+		let bid = |score, trump| Some(Bid { trump, score });
+		assert_eq!(parse_bid("80 h"), bid(BidScore::_80, Trump::Suit(Suit::Hearts)));
+		assert_eq!(parse_bid("160 s"), bid(BidScore::_160, Trump::Suit(Suit::Spades)));
+		assert_eq!(parse_bid("capot a"), bid(BidScore::Capot, Trump::NoTrump));
+		assert_eq!(parse_bid("100 t"), bid(BidScore::_100, Trump::AllTrump));
+		assert_eq!(parse_bid("90  c"), bid(BidScore::_90, Trump::Suit(Suit::Clubs)));
+	}
+
+	#[test]
+	fn invalid_bids_are_rejected() {
+		for command in ["80", "85 h", "80 x", "80 hh", "h 80", "pass", ""] {
+			assert_eq!(parse_bid(command), None, "{:?} should not parse", command);
+		}
+	}
+}
@@ -0,0 +1,85 @@
+use {
+	std::{
+		io,
+		net::TcpStream,
+		sync::mpsc::{self, Receiver, Sender, TryRecvError},
+		thread,
+		time::Duration,
+	},
+	tungstenite::{stream::MaybeTlsStream, Message, WebSocket},
+};
+
+/// How often the socket thread checks for messages to send while waiting for the server
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub enum NetEvent {
+	Message(String),
+	/// With the reason why
+	Closed(String),
+}
+
+/// Websocket run on a thread of its own, so that the UI never blocks on it
+pub struct Connection {
+	pub events: Receiver<NetEvent>,
+	outgoing: Sender<String>,
+}
+
+impl Connection {
+	pub fn open(url: &str) -> coinche::Result<Self> {
+		let (socket, _response) = tungstenite::connect(url)?;
+		let stream = match socket.get_ref() {
+			MaybeTlsStream::Plain(stream) => stream,
+			MaybeTlsStream::Rustls(stream) => &stream.sock,
+			_ => return Err(failure::err_msg("Unsupported stream")),
+		};
+		stream.set_read_timeout(Some(POLL_INTERVAL))?;
+		let (events_sender, events) = mpsc::channel();
+		let (outgoing, outgoing_receiver) = mpsc::channel();
+		thread::spawn(move || {
+			let reason = run(socket, &events_sender, &outgoing_receiver);
+			let _ = events_sender.send(NetEvent::Closed(reason));
+		});
+		Ok(Self { events, outgoing })
+	}
+
+	pub fn send(&self, json: String) {
+		// Closing is reported through `events`
+		let _ = self.outgoing.send(json);
+	}
+}
+
+fn run(
+	mut socket: WebSocket<MaybeTlsStream<TcpStream>>,
+	events: &Sender<NetEvent>,
+	outgoing: &Receiver<String>,
+) -> String {
+	loop {
+		loop {
+			match outgoing.try_recv() {
+				Ok(json) => {
+					if let Err(err) = socket.send(Message::text(json)) {
+						return err.to_string();
+					}
+				}
+				Err(TryRecvError::Empty) => break,
+				Err(TryRecvError::Disconnected) => {
+					let _ = socket.close(None);
+					return "Connection closed".to_owned();
+				}
+			}
+		}
+		match socket.read() {
+			Ok(Message::Text(json)) => {
+				if events.send(NetEvent::Message(json.as_str().to_owned())).is_err() {
+					return "Connection closed".to_owned();
+				}
+			}
+			Ok(Message::Close(_)) => return "Connection closed by the server".to_owned(),
+			// Pings are answered by tungstenite
+			Ok(_) => (),
+			Err(tungstenite::Error::Io(err))
+				if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+			Err(err) => return err.to_string(),
+		}
+	}
+}
@@ -0,0 +1,46 @@
+//! Terminal client: `coinche-tui <username> [url] [password]`
+mod app;
+mod connection;
+mod ui;
+
+use {
+	app::App,
+	connection::Connection,
+	ratatui::crossterm::event::{self, Event, KeyEventKind},
+	std::{env, time::Duration},
+};
+
+const DEFAULT_URL: &str = "ws://localhost:3000/ws/";
+/// How long to wait for a key press before checking the server messages
+const TICK: Duration = Duration::from_millis(50);
+
+fn main() -> coinche::Result<()> {
+	let mut args = env::args().skip(1);
+	let username = args
+		.next()
+		.ok_or_else(|| failure::err_msg("Usage: coinche-tui <username> [url] [password]"))?;
+	let url = args.next().unwrap_or_else(|| DEFAULT_URL.to_owned());
+	let password = args.next();
+
+	let connection = Connection::open(&url)?;
+	let mut app = App::new(connection, url, username, password);
+	let mut terminal = ratatui::init();
+	let res = run(&mut terminal, &mut app);
+	ratatui::restore();
+	res
+}
+
+fn run(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> coinche::Result<()> {
+	while !app.quit {
+		terminal.draw(|frame| ui::draw(frame, app))?;
+		if event::poll(TICK)? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind == KeyEventKind::Press {
+					app.on_key(key);
+				}
+			}
+		}
+		app.poll_connection();
+	}
+	Ok(())
+}
@@ -0,0 +1,254 @@
+use {
+	super::app::{bid_name, App, Phase, Table},
+	coinche::{
+		game::{
+			cards::{Card, Suit},
+			contract::PlayerBid,
+			turn::Turn,
+			view::Action,
+			CoincheState,
+		},
+		player::{Control, Player},
+	},
+	ratatui::{
+		layout::{Constraint, Layout, Rect},
+		style::{Color, Modifier, Style},
+		text::{Line, Span},
+		widgets::{Block, Paragraph, Wrap},
+		Frame,
+	},
+};
+
+pub fn draw(frame: &mut Frame, app: &App) {
+	let [header, main, hand, input] = Layout::vertical([
+		Constraint::Length(1),
+		Constraint::Min(11),
+		Constraint::Length(3),
+		Constraint::Length(3),
+	])
+	.areas(frame.area());
+	let [table_area, log] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(main);
+
+	frame.render_widget(Paragraph::new(header_line(app)), header);
+	match &app.table {
+		Some(table) => draw_table(frame, table_area, table),
+		None => frame.render_widget(
+			Paragraph::new("Joining the table...").block(Block::bordered().title("Table")),
+			table_area,
+		),
+	}
+	draw_log(frame, log, app);
+	draw_hand(frame, hand, app);
+	frame.render_widget(
+		Paragraph::new(format!("> {}", app.input)).block(Block::bordered().title(actions_hint(app))),
+		input,
+	);
+}
+
+fn header_line(app: &App) -> Line<'static> {
+	let mut spans = vec![Span::styled(
+		format!(" Coinche - {} ", app.username),
+		Style::new().add_modifier(Modifier::BOLD),
+	)];
+	if let Some(table) = &app.table {
+		let team = Player::team(table.player_id) as usize;
+		spans.push(Span::raw(format!(
+			"  Us {} - Them {}",
+			table.points[team],
+			table.points[1 - team]
+		)));
+	}
+	Line::from(spans)
+}
+
+fn draw_table(frame: &mut Frame, area: Rect, table: &Table) {
+	let block = Block::bordered().title("Table");
+	let inner = block.inner(area);
+	frame.render_widget(block, area);
+	let [top, middle, bottom] =
+		Layout::vertical([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)]).areas(inner);
+	let [left, center, right] = Layout::horizontal([
+		Constraint::Percentage(30),
+		Constraint::Percentage(40),
+		Constraint::Percentage(30),
+	])
+	.areas(middle);
+	// Play goes around clockwise, starting on our left
+	for (offset, seat_area) in [bottom, left, top, right].into_iter().enumerate() {
+		let player_id = (table.player_id + offset) % 4;
+		frame.render_widget(Paragraph::new(seat_lines(table, player_id)).centered(), seat_area);
+	}
+	frame.render_widget(
+		Paragraph::new(center_lines(table)).centered().wrap(Wrap { trim: true }),
+		center,
+	);
+}
+
+fn seat_lines(table: &Table, player_id: usize) -> Vec<Line<'static>> {
+	let seat = match table.players.get(player_id) {
+		Some(seat) => seat,
+		None => return vec![Line::styled("(empty seat)", Style::new().fg(Color::DarkGray))],
+	};
+	let mut name = seat.username.clone();
+	if player_id == table.dealer_id {
+		name.push_str(" (D)");
+	}
+	match seat.control {
+		Control::Human => (),
+		Control::Bot => name.push_str(" [bot]"),
+		Control::Disconnected => name.push_str(" [disconnected]"),
+		Control::Vacant => name.push_str(" [left]"),
+	}
+	let style = match table.turn.is_some_and(|turn| turn_includes(turn, player_id)) {
+		true => Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+		false => Style::new(),
+	};
+	let mut lines = vec![
+		Line::styled(name, style),
+		Line::styled(format!("{} cards", seat.card_count), Style::new().fg(Color::DarkGray)),
+	];
+	match &table.phase {
+		Phase::Lobby => (),
+		Phase::Bidding { bids } => {
+			if let Some(PlayerBid { bid, .. }) = bids.iter().rev().find(|b| b.player_id == player_id) {
+				lines.push(Line::raw(bid.map_or_else(|| "Pass".to_owned(), bid_name)));
+			}
+		}
+		Phase::Playing { board, .. } => {
+			let played = board
+				.cards
+				.iter()
+				.enumerate()
+				.find(|(i, _)| (board.starting_player_id + i) % 4 == player_id);
+			if let Some((_, &card)) = played {
+				lines.push(Line::from(card_span(card, Style::new().add_modifier(Modifier::BOLD))));
+			}
+		}
+	}
+	lines
+}
+
+fn turn_includes(turn: Turn, player_id: usize) -> bool {
+	match turn {
+		Turn::Bid { player_id: id } | Turn::PlayCard { player_id: id } => id == player_id,
+		Turn::SurCoinche { team } => Player::team(player_id) == team,
+	}
+}
+
+fn center_lines(table: &Table) -> Vec<Line<'static>> {
+	let username = |player_id: usize| table.players.get(player_id).map_or("?", |seat| seat.username.as_str());
+	match &table.phase {
+		Phase::Lobby => vec![Line::raw(format!("Waiting for players ({}/4)", table.players.len()))],
+		Phase::Bidding { .. } => vec![Line::raw("Bidding")],
+		Phase::Playing {
+			bidder_id,
+			bid,
+			coinche_state,
+			last_trick,
+			tricks,
+			..
+		} => {
+			let team = Player::team(table.player_id) as usize;
+			let coinche = match coinche_state {
+				CoincheState::No => "",
+				CoincheState::Coinche { .. } => ", coinched",
+				CoincheState::Surcoinche { .. } => ", surcoinched",
+			};
+			let mut lines = vec![
+				Line::raw(format!("{}: {}{}", username(*bidder_id), bid_name(*bid), coinche)),
+				Line::raw(format!("Tricks: us {} - them {}", tricks[team], tricks[1 - team])),
+			];
+			if let Some(trick) = last_trick {
+				let mut spans = vec![Span::raw("Last trick: ")];
+				for &card in trick.cards.iter() {
+					spans.push(card_span(card, Style::new()));
+					spans.push(Span::raw(" "));
+				}
+				spans.push(Span::raw(format!("({})", username(trick.winner_id))));
+				lines.push(Line::from(spans));
+			}
+			lines
+		}
+	}
+}
+
+/// Latest lines that fit, wrapped
+fn draw_log(frame: &mut Frame, area: Rect, app: &App) {
+	let (width, height) = (
+		area.width.saturating_sub(2).max(1) as usize,
+		area.height.saturating_sub(2) as usize,
+	);
+	let mut rows = 0;
+	let first = app
+		.log
+		.iter()
+		.rposition(|line| {
+			rows += line.chars().count().div_ceil(width).max(1);
+			rows > height
+		})
+		.map_or(0, |pos| pos + 1);
+	let lines: Vec<Line> = app.log[first..].iter().map(|line| Line::raw(line.as_str())).collect();
+	frame.render_widget(
+		Paragraph::new(lines)
+			.block(Block::bordered().title("Log"))
+			.wrap(Wrap { trim: false }),
+		area,
+	);
+}
+
+fn draw_hand(frame: &mut Frame, area: Rect, app: &App) {
+	let hand = app.table.as_ref().map_or(&[][..], |table| table.hand.as_slice());
+	let playable = app.playable_cards();
+	let mut spans = Vec::new();
+	for (pos, &card) in hand.iter().enumerate() {
+		let mut style = Style::new();
+		if playable.is_some_and(|cards| !cards.contains(&card)) {
+			style = style.add_modifier(Modifier::DIM);
+		}
+		if pos == app.selected {
+			style = style.add_modifier(Modifier::REVERSED);
+		}
+		spans.push(Span::raw(" "));
+		spans.push(card_span(card, style));
+	}
+	frame.render_widget(
+		Paragraph::new(Line::from(spans)).block(Block::bordered().title("Hand")),
+		area,
+	);
+}
+
+/// In the `Card` `Display` format, red for hearts and diamonds
+fn card_span(card: Card, style: Style) -> Span<'static> {
+	let style = match card.suit {
+		Suit::Hearts | Suit::Diamonds => style.fg(Color::Red),
+		Suit::Spades | Suit::Clubs => style,
+	};
+	Span::styled(card.to_string(), style)
+}
+
+/// What may be typed right now
+fn actions_hint(app: &App) -> String {
+	let actions = app
+		.table
+		.as_ref()
+		.map_or(&[][..], |table| table.legal_actions.as_slice());
+	let hints: Vec<&str> = actions
+		.iter()
+		.map(|action| match action {
+			Action::Bid { .. } => "<score> <trump>",
+			Action::Pass => "pass",
+			Action::Coinche => "coinche",
+			Action::SurCoinche => "surcoinche/pass",
+			Action::PlayCard { .. } => "Enter: play",
+			Action::Claim => "claim",
+			Action::AnswerClaim | Action::AnswerUndo => "yes/no",
+			Action::RequestUndo => "undo",
+			Action::RequestRedeal => "redeal",
+			Action::Resume => "resume",
+		})
+		.collect();
+	match hints.is_empty() {
+		true => "Command (help)".to_owned(),
+		false => format!("Command: {}", hints.join(" | ")),
+	}
+}
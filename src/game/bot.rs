@@ -0,0 +1,55 @@
+use crate::prelude::*;
+
+use std::ops::Deref;
+
+impl Game {
+	/// Acts for the bot-controlled seats among those `turn` is waiting for
+	pub fn play_bot_turn(&mut self, turn: Turn) -> crate::Result<()> {
+		for player_id in self.turn_player_ids(turn) {
+			if !self.players[player_id].control.is_bot() {
+				continue;
+			}
+			match turn {
+				Turn::Bid { .. } => self.player_mut(player_id).bid(None)?,
+				Turn::SurCoinche { .. } => self.player_mut(player_id).surcoincher(false)?,
+				Turn::PlayCard { .. } => {
+					let card = self
+						.player(player_id)
+						.bot_card()
+						.ok_or_else(|| err_msg("No playable card"))?;
+					self.player_mut(player_id).play_card(PlayerCardIdentifier::Card(card))?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<G: Deref<Target = Game>> PlayerPtr<G> {
+	/// Takes the trick as cheaply as possible unless partner already holds it, otherwise discards the cheapest card
+	pub fn bot_card(&self) -> Option<Card> {
+		let running = match &self.game.game_state {
+			GameState::Running(running) => running,
+			_ => return None,
+		};
+		let trump = running.bid.trump;
+		let partner_winning = running
+			.board
+			.winning_player_id(trump)
+			.is_some_and(|winner_id| Player::team(winner_id) == self.team());
+		if !partner_winning {
+			let winning_card = self
+				.playable_cards()
+				.filter(|&card| {
+					let mut board = running.board.clone();
+					board.cards.push(card);
+					board.winning_player_id(trump) == Some(self.player_id)
+				})
+				.min_by(|a, b| a.cmp_points(*b, trump));
+			if winning_card.is_some() {
+				return winning_card;
+			}
+		}
+		self.lowest_playable_card()
+	}
+}
@@ -1,6 +1,10 @@
-use std::{cmp::Ordering, fmt};
+use {
+	schemars::JsonSchema,
+	serde::{Deserialize, Deserializer, Serialize, Serializer},
+	std::{cmp::Ordering, fmt, str::FromStr},
+};
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum Suit {
 	Spades,
 	Hearts,
@@ -23,14 +27,12 @@ impl Suit {
 			'h' => Suit::Hearts,
 			'd' => Suit::Diamonds,
 			'c' => Suit::Clubs,
-			_ => {
-				return None;
-			}
+			_ => return None,
 		})
 	}
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum Value {
 	Seven,
 	Eight,
@@ -56,6 +58,20 @@ impl Value {
 		}
 	}
 
+	pub fn from_char(c: char) -> Option<Value> {
+		Some(match c {
+			'7' => Value::Seven,
+			'8' => Value::Eight,
+			'9' => Value::Nine,
+			'J' => Value::Jack,
+			'Q' => Value::Queen,
+			'K' => Value::King,
+			'T' => Value::Ten,
+			'A' => Value::Ace,
+			_ => return None,
+		})
+	}
+
 	pub fn cmp_trump(&self, other: &Value) -> Ordering {
 		use Value::*;
 		match (self, other) {
@@ -71,12 +87,53 @@ impl Value {
 }
 
 /// An unnamed tuple with Value and Suit.
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+///
+/// Serialized as its `Deck` byte by binary formats.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, JsonSchema)]
 pub struct Card {
 	pub value: Value,
 	pub suit: Suit,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Card")]
+struct CardFields {
+	value: Value,
+	suit: Suit,
+}
+
+/// Either form is accepted, whatever the format
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CardRepr {
+	Byte(u8),
+	Fields(CardFields),
+}
+
+impl Serialize for Card {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match serializer.is_human_readable() {
+			true => CardFields {
+				value: self.value,
+				suit: self.suit,
+			}
+			.serialize(serializer),
+			false => serializer.serialize_u8(self.to_byte()),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Card {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		match CardRepr::deserialize(deserializer)? {
+			CardRepr::Byte(byte) => {
+				Card::from_byte(byte).ok_or_else(|| serde::de::Error::custom(format!("invalid card byte {}", byte)))
+			}
+			CardRepr::Fields(CardFields { value, suit }) => Ok(Card { value, suit }),
+		}
+	}
+}
+
 impl Card {
 	pub fn new(value: Value, suit: Suit) -> Card {
 		Card { value, suit }
@@ -89,3 +146,49 @@ impl fmt::Display for Card {
 		write!(f, "{}{}", self.value.as_char(), self.suit.as_char())
 	}
 }
+
+/// Reads the `Display` format back, e.g. "Jh" or "Ts"
+impl FromStr for Card {
+	type Err = failure::Error;
+	fn from_str(s: &str) -> crate::Result<Self> {
+		let mut chars = s.chars();
+		match (chars.next(), chars.next(), chars.next()) {
+			(Some(value), Some(suit), None) => Ok(Card::new(
+				Value::from_char(value.to_ascii_uppercase()).ok_or_else(|| failure::err_msg("Unknown card value"))?,
+				Suit::from_char(suit.to_ascii_lowercase()).ok_or_else(|| failure::err_msg("Unknown suit"))?,
+			)),
+			_ => Err(failure::err_msg(
+				"A card is written as its value then its suit, e.g. Jh",
+			)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cards_parse_from_their_display_format() {
+		use Value::*;
+		for value in [Seven, Eight, Nine, Jack, Queen, King, Ten, Ace] {
+			for suit in [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
+				let card = Card::new(value, suit);
+				assert_eq!(card.to_string().parse::<Card>().unwrap(), card);
+			}
+		}
+	}
+
+	#[test]
+	fn card_parsing_ignores_case() {
+		assert_eq!("jH".parse::<Card>().unwrap(), Card::new(Value::Jack, Suit::Hearts));
+		assert_eq!("tc".parse::<Card>().unwrap(), Card::new(Value::Ten, Suit::Clubs));
+	}
+
+	#[test]
+	fn invalid_cards_are_rejected() {
+		for s in ["", "J", "Jhh", "1h", "Jx", "hJ", "80"] {
+			assert!(s.parse::<Card>().is_err(), "{:?} should not parse", s);
+		}
+	}
+}
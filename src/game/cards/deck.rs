@@ -1,4 +1,4 @@
-use rand::{self, seq::SliceRandom};
+use rand::{self, rngs::StdRng, seq::SliceRandom, SeedableRng};
 
 use super::card::{Card, Suit, Value};
 
@@ -39,6 +39,41 @@ fn create_card_for_value(value: u8) -> Card {
 	Card::new(value, suit)
 }
 
+/// Inverse of `create_card_for_value`
+fn value_for_card(card: Card) -> u8 {
+	let suit = match card.suit {
+		Suit::Spades => 0,
+		Suit::Hearts => 1,
+		Suit::Diamonds => 2,
+		Suit::Clubs => 3,
+	};
+	let value = match card.value {
+		Value::Seven => 0,
+		Value::Eight => 1,
+		Value::Nine => 2,
+		Value::Ten => 3,
+		Value::Jack => 4,
+		Value::Queen => 5,
+		Value::King => 6,
+		Value::Ace => 7,
+	};
+	suit * 8 + value
+}
+
+impl Card {
+	/// Compact encoding, as the `Deck` stores cards
+	pub fn to_byte(self) -> u8 {
+		value_for_card(self)
+	}
+
+	pub fn from_byte(byte: u8) -> Option<Card> {
+		match byte < 32 {
+			true => Some(create_card_for_value(byte)),
+			false => None,
+		}
+	}
+}
+
 /// A deck can be dealt from and shuffled.
 impl Deck {
 	/// Returns a deck where all cards are sorted by Suit, then by Value.
@@ -48,10 +83,8 @@ impl Deck {
 			cards: [0; 32],
 		};
 
-		let mut value = 0;
-		for x in d.cards.iter_mut() {
-			*x = value;
-			value += 1;
+		for (value, x) in d.cards.iter_mut().enumerate() {
+			*x = value as u8;
 		}
 		d
 	}
@@ -63,6 +96,22 @@ impl Deck {
 		d
 	}
 
+	/// Always shuffled the same way for a given seed.
+	pub fn new_seeded(seed: u64) -> Deck {
+		let mut d = Deck::new_unshuffled();
+		d.cards.shuffle(&mut StdRng::seed_from_u64(seed));
+		d
+	}
+
+	/// A deck dealing the given hands in order, when drawn by 8. The hands should hold the 32 cards.
+	pub fn from_hands(hands: &[Vec<Card>]) -> Deck {
+		let mut d = Deck::new_unshuffled();
+		for (x, &card) in d.cards.iter_mut().zip(hands.iter().flatten()) {
+			*x = value_for_card(card);
+		}
+		d
+	}
+
 	/// Just pretend nothing was ever dealt.
 	pub fn reset_unshuffled(&mut self) {
 		self.count_dealt = 0;
@@ -0,0 +1,143 @@
+use crate::prelude::*;
+
+use std::time::{Duration, Instant};
+
+/// Messages kept for players joining or reconnecting
+const CHAT_HISTORY_LEN: usize = 50;
+const CHAT_MAX_LEN: usize = 300;
+/// At most `CHAT_RATE_LIMIT` messages per player in any `CHAT_RATE_WINDOW`
+const CHAT_RATE_LIMIT: usize = 5;
+const CHAT_RATE_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ChatMessage {
+	pub player_id: usize,
+	pub username: String,
+	pub text: String,
+	/// Unix timestamp (ms)
+	pub timestamp: i64,
+}
+
+impl Game {
+	pub fn chat(&mut self, player_id: usize, text: String) -> GameResult<()> {
+		let text = text.trim();
+		if text.is_empty() {
+			return Err(GameError::EmptyMessage);
+		}
+		if text.chars().count() > CHAT_MAX_LEN {
+			return Err(GameError::MessageTooLong { max_len: CHAT_MAX_LEN });
+		}
+		let player = &mut self.players[player_id];
+		if player.silenced {
+			return Err(GameError::Silenced);
+		}
+		let now = Instant::now();
+		player.chat_times.retain(|&t| now.duration_since(t) < CHAT_RATE_WINDOW);
+		if player.chat_times.len() >= CHAT_RATE_LIMIT {
+			return Err(GameError::TooManyMessages);
+		}
+		player.chat_times.push(now);
+		let message = ChatMessage {
+			player_id,
+			username: player.username.clone(),
+			text: filter_words(text, &self.settings.chat_word_filter),
+			timestamp: chrono::Utc::now().timestamp_millis(),
+		};
+		for player in self.players.iter() {
+			if !player.muted.contains(&player_id) {
+				let _ = player.send(ServerMessage::Chat(&message));
+			}
+		}
+		if self.chat_history.len() >= CHAT_HISTORY_LEN {
+			self.chat_history.remove(0);
+		}
+		self.chat_history.push(message);
+		Ok(())
+	}
+
+	/// Hides another player's messages from this player only
+	pub fn mute(&mut self, player_id: usize, muted_player_id: usize, muted: bool) -> GameResult<()> {
+		if muted_player_id >= self.players.len() || muted_player_id == player_id {
+			return Err(GameError::InvalidPlayer {
+				player_id: muted_player_id,
+			});
+		}
+		let muted_ids = &mut self.players[player_id].muted;
+		muted_ids.retain(|&id| id != muted_player_id);
+		if muted {
+			muted_ids.push(muted_player_id);
+		}
+		let _ = self.player(player_id).send_chat_history();
+		Ok(())
+	}
+
+	/// Prevents a player from chatting at this table
+	pub fn silence(&mut self, player_id: usize, silenced: bool) -> GameResult<()> {
+		let player = self
+			.players
+			.get_mut(player_id)
+			.ok_or(GameError::InvalidPlayer { player_id })?;
+		player.silenced = silenced;
+		self.send_all(ServerMessage::Silenced { player_id, silenced });
+		Ok(())
+	}
+}
+
+/// Replaces filtered words with asterisks, ignoring case
+fn filter_words(text: &str, word_filter: &[String]) -> String {
+	if word_filter.is_empty() {
+		return text.to_owned();
+	}
+	let mut filtered = String::with_capacity(text.len());
+	let mut word = String::new();
+	let flush_word = |word: &mut String, filtered: &mut String| {
+		let lowercase = word.to_lowercase();
+		if word_filter.iter().any(|w| w.to_lowercase() == lowercase) {
+			filtered.extend(std::iter::repeat_n('*', word.chars().count()));
+		} else {
+			filtered.push_str(word);
+		}
+		word.clear();
+	};
+	for c in text.chars() {
+		if c.is_alphanumeric() {
+			word.push(c);
+		} else {
+			flush_word(&mut word, &mut filtered);
+			filtered.push(c);
+		}
+	}
+	flush_word(&mut word, &mut filtered);
+	filtered
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn filter(text: &str, words: &[&str]) -> String {
+		let word_filter: Vec<String> = words.iter().map(|&w| w.to_owned()).collect();
+		filter_words(text, &word_filter)
+	}
+
+	#[test]
+	fn filtered_words_are_masked_whatever_their_case() {
+		assert_eq!(filter("Zut, ZUT et zut!", &["zut"]), "***, *** et ***!");
+		assert_eq!(filter("Bon jeu", &["JEU"]), "Bon ***");
+	}
+
+	#[test]
+	fn only_whole_words_are_masked() {
+		assert_eq!(filter("zutique zut", &["zut"]), "zutique ***");
+	}
+
+	#[test]
+	fn masks_keep_the_length_in_characters() {
+		assert_eq!(filter("Mince, été raté", &["été"]), "Mince, *** raté");
+	}
+
+	#[test]
+	fn no_filter_keeps_the_text() {
+		assert_eq!(filter("Zut !", &[]), "Zut !");
+	}
+}
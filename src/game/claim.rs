@@ -0,0 +1,248 @@
+use crate::prelude::*;
+
+/// Bounds the time spent checking a claim, opponents are asked to accept it beyond that
+const CLAIM_SEARCH_BUDGET: usize = 200_000;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Claim {
+	pub player_id: usize,
+	/// Opponents who accepted the claim so far
+	pub accepted_by: Vec<usize>,
+}
+
+impl Game {
+	/// "Je fais le reste": the claimer's team takes all the remaining tricks
+	pub fn claim(&mut self, player_id: usize) -> GameResult<()> {
+		let running = match &self.game_state {
+			GameState::Running(running) => running,
+			_ => {
+				return Err(GameError::WrongPhase {
+					expected: Phase::Running,
+				})
+			}
+		};
+		if running.claim.is_some() {
+			return Err(GameError::ClaimPending);
+		}
+		let mut hands: [Vec<Card>; 4] = Default::default();
+		for (hand, player) in hands.iter_mut().zip(self.players.iter()) {
+			hand.clone_from(&player.cards);
+		}
+		let mut search = ClaimSearch {
+			trump: running.bid.trump,
+			team: Player::team(player_id),
+			budget: CLAIM_SEARCH_BUDGET,
+		};
+		let guaranteed = search.search(&mut hands, &mut running.board.clone());
+		self.send_all(ServerMessage::Claim {
+			player_id,
+			cards: &self.players[player_id].cards,
+			pending: guaranteed.is_none(),
+		});
+		match guaranteed {
+			Some(true) => self.resolve_claim(player_id, true),
+			Some(false) => {
+				self.send_all(ServerMessage::ClaimResult {
+					player_id,
+					accepted: false,
+				});
+				Err(GameError::ClaimRefuted)
+			}
+			None => {
+				if let GameState::Running(running) = &mut self.game_state {
+					running.claim = Some(Claim {
+						player_id,
+						accepted_by: Vec::new(),
+					});
+				}
+				// Absent opponents can't object
+				self.answer_claim_as_absent()
+			}
+		}
+	}
+
+	pub fn answer_claim(&mut self, player_id: usize, accept: bool) -> GameResult<()> {
+		let claim = match &mut self.game_state {
+			GameState::Running(RunningGame { claim: Some(claim), .. }) => claim,
+			_ => return Err(GameError::NoPendingClaim),
+		};
+		if Player::team(player_id) == Player::team(claim.player_id) {
+			return Err(GameError::NotOpponent);
+		}
+		let claimer_id = claim.player_id;
+		if !accept {
+			return self.resolve_claim(claimer_id, false);
+		}
+		if !claim.accepted_by.contains(&player_id) {
+			claim.accepted_by.push(player_id);
+		}
+		self.answer_claim_as_absent()
+	}
+
+	fn answer_claim_as_absent(&mut self) -> GameResult<()> {
+		let claim = match &self.game_state {
+			GameState::Running(RunningGame { claim: Some(claim), .. }) => claim,
+			_ => return Ok(()),
+		};
+		let present_opponents: Vec<usize> = self
+			.player_ids()
+			.filter(|&id| {
+				Player::team(id) != Player::team(claim.player_id) && self.players[id].control == Control::Human
+			})
+			.collect();
+		// Claims that couldn't be proven need an opponent's word
+		if present_opponents.is_empty() && claim.accepted_by.is_empty() {
+			return self.resolve_claim(claim.player_id, false);
+		}
+		let all_accepted = present_opponents.iter().all(|id| claim.accepted_by.contains(id));
+		match all_accepted {
+			true => self.resolve_claim(claim.player_id, true),
+			false => Ok(()),
+		}
+	}
+
+	/// Gives all the remaining cards to the claimer's team if accepted, then scores the deal
+	fn resolve_claim(&mut self, player_id: usize, accepted: bool) -> GameResult<()> {
+		let running = match &mut self.game_state {
+			GameState::Running(running) => running,
+			_ => {
+				return Err(GameError::WrongPhase {
+					expected: Phase::Running,
+				})
+			}
+		};
+		running.claim = None;
+		self.send_all(ServerMessage::ClaimResult { player_id, accepted });
+		if !accepted {
+			return Ok(());
+		}
+		let running = match &mut self.game_state {
+			GameState::Running(running) => running,
+			_ => unreachable!(),
+		};
+		if let (Trump::Suit(trump_suit), None) = (running.bid.trump, running.belote_player) {
+			running.belote_player = self.players.iter().position(|p| {
+				p.cards.contains(&Card::new(Value::King, trump_suit))
+					&& p.cards.contains(&Card::new(Value::Queen, trump_suit))
+			});
+		}
+		let mut remaining_cards = std::mem::take(&mut running.board.cards);
+		for player in self.players.iter_mut() {
+			remaining_cards.append(&mut player.cards);
+		}
+		let mut starting_player_id = running.board.starting_player_id;
+		for cards in remaining_cards.chunks(4) {
+			running.tricks.push(Trick {
+				starting_player_id,
+				winner_id: player_id,
+				cards: cards.to_vec(),
+			});
+			starting_player_id = player_id;
+		}
+		running.board.starting_player_id = player_id;
+		self.try_end();
+		Ok(())
+	}
+}
+
+struct ClaimSearch {
+	trump: Trump,
+	team: bool,
+	budget: usize,
+}
+
+impl ClaimSearch {
+	/// Whether the team can take every remaining trick whatever the opponents play,
+	/// `None` if that couldn't be decided within budget
+	fn search(&mut self, hands: &mut [Vec<Card>; 4], board: &mut Board) -> Option<bool> {
+		if self.budget == 0 {
+			return None;
+		}
+		self.budget -= 1;
+		if board.cards.len() == 4 {
+			let winner_id = board.winning_player_id(self.trump).unwrap();
+			if Player::team(winner_id) != self.team {
+				return Some(false);
+			}
+			let mut next_board = Board {
+				starting_player_id: winner_id,
+				cards: Vec::new(),
+			};
+			return self.search(hands, &mut next_board);
+		}
+		let player_id = (board.starting_player_id + board.cards.len()) % 4;
+		if hands[player_id].is_empty() {
+			return Some(true);
+		}
+		let is_team = Player::team(player_id) == self.team;
+		let mut undecided = false;
+		for pos in 0..hands[player_id].len() {
+			let card = hands[player_id][pos];
+			if !board.can_play(self.trump, &hands[player_id], card, Player::team(player_id)) {
+				continue;
+			}
+			hands[player_id].remove(pos);
+			board.cards.push(card);
+			let res = self.search(hands, board);
+			board.cards.pop();
+			hands[player_id].insert(pos, card);
+			match (res, is_team) {
+				// The team picks its best card, opponents their best defense
+				(Some(true), true) => return Some(true),
+				(Some(false), false) => return Some(false),
+				(None, _) => undecided = true,
+				_ => (),
+			}
+		}
+		match undecided {
+			true => None,
+			false => Some(!is_team),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hands(hands: [&str; 4]) -> [Vec<Card>; 4] {
+		hands.map(|hand| hand.split_whitespace().map(|card| card.parse().unwrap()).collect())
+	}
+
+	fn search(mut hands: [Vec<Card>; 4], claimer_id: usize, budget: usize) -> Option<bool> {
+		let mut search = ClaimSearch {
+			trump: Trump::Suit(Suit::Hearts),
+			team: Player::team(claimer_id),
+			budget,
+		};
+		let mut board = Board {
+			starting_player_id: claimer_id,
+			cards: Vec::new(),
+		};
+		search.search(&mut hands, &mut board)
+	}
+
+	#[test]
+	fn master_trumps_take_the_rest() {
+		let hands = hands(["Jh 9h", "7s 8s", "7c 8c", "7d 8d"]);
+		assert_eq!(search(hands, 0, CLAIM_SEARCH_BUDGET), Some(true));
+	}
+
+	#[test]
+	fn partner_can_take_tricks() {
+		let hands = hands(["7s", "8s", "As", "9s"]);
+		assert_eq!(search(hands, 0, CLAIM_SEARCH_BUDGET), Some(true));
+	}
+
+	#[test]
+	fn opponents_taking_a_trick_refute_the_claim() {
+		let hands = hands(["Jh 7s", "7c As", "8c 8s", "9c 9s"]);
+		assert_eq!(search(hands, 0, CLAIM_SEARCH_BUDGET), Some(false));
+	}
+
+	#[test]
+	fn running_out_of_budget_is_undecided() {
+		let hands = hands(["Jh 9h", "7s 8s", "7c 8c", "7d 8d"]);
+		assert_eq!(search(hands, 0, 2), None);
+	}
+}
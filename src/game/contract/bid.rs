@@ -1,18 +1,18 @@
 use crate::prelude::*;
 
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, JsonSchema)]
 pub struct PlayerBid {
 	pub player_id: usize,
 	pub bid: Option<Bid>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Bid {
 	pub trump: Trump,
 	pub score: BidScore,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, JsonSchema)]
 pub enum BidScore {
 	#[serde(rename = "80")]
 	_80,
@@ -41,6 +41,7 @@ pub enum BidScore {
 }
 
 impl BidScore {
+	#[allow(clippy::just_underscores_and_digits)]
 	pub fn required_points(self) -> usize {
 		use BidScore::*;
 		match self {
@@ -0,0 +1,136 @@
+use crate::prelude::*;
+
+/// Deals are drawn from a seed shared with other tables, so that the same hands get played at each of them
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Duplicate {
+	/// Hidden, as it gives away every hand
+	#[serde(skip)]
+	pub seed: u64,
+	/// Hands are moved one seat over: East/West hold what North/South hold at the other tables
+	pub swapped: bool,
+	/// Number of deals in the match
+	pub boards: usize,
+	/// Points of each seat team on each board played so far, `[0, 0]` if nobody bid
+	pub results: Vec<[usize; 2]>,
+}
+
+impl Duplicate {
+	pub fn new(seed: u64, swapped: bool, boards: usize) -> Self {
+		Self {
+			seed,
+			swapped,
+			boards,
+			results: Vec::new(),
+		}
+	}
+
+	/// Board being played, or to be dealt next
+	pub fn board(&self) -> usize {
+		self.results.len()
+	}
+
+	/// Deck dealing the hands of the board, in seat order
+	pub fn deck(&self, board: usize) -> Deck {
+		let mut deck = Deck::new_seeded(self.seed.wrapping_add(board as u64));
+		let mut hands: Vec<Vec<Card>> = (0..4).map(|_| deck.draw_n(32 / 4).unwrap()).collect();
+		if self.swapped {
+			hands.rotate_right(1);
+		}
+		Deck::from_hands(&hands)
+	}
+
+	/// Dealers rotate with the hands
+	pub fn dealer_id(&self, board: usize) -> usize {
+		(board + self.swapped as usize) % 4
+	}
+
+	/// Points of the North/South hands and East/West hands on each board
+	pub fn results_by_hands(&self) -> Vec<[usize; 2]> {
+		self.results
+			.iter()
+			.map(|&[p0, p1]| match self.swapped {
+				false => [p0, p1],
+				true => [p1, p0],
+			})
+			.collect()
+	}
+}
+
+/// Matchpoint percentage of the North/South hands at each table, from their results on each board.
+/// On every board, a table scores 1 against each other table it did better than and 0.5 on ties.
+/// East/West get the rest.
+pub fn matchpoints(tables: &[Vec<[usize; 2]>]) -> Vec<f64> {
+	if tables.len() < 2 {
+		return vec![50.; tables.len()];
+	}
+	let boards = tables.iter().map(Vec::len).min().unwrap_or(0);
+	if boards == 0 {
+		return vec![50.; tables.len()];
+	}
+	let net = |table: usize, board: usize| {
+		let [ns, ew] = tables[table][board];
+		ns as i64 - ew as i64
+	};
+	(0..tables.len())
+		.map(|table| {
+			let scored: f64 = (0..boards)
+				.flat_map(|board| {
+					(0..tables.len())
+						.filter(move |&other| other != table)
+						.map(move |o| (board, o))
+				})
+				.map(|(board, other)| match net(table, board).cmp(&net(other, board)) {
+					std::cmp::Ordering::Greater => 1.,
+					std::cmp::Ordering::Equal => 0.5,
+					std::cmp::Ordering::Less => 0.,
+				})
+				.sum();
+			100. * scored / (boards * (tables.len() - 1)) as f64
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn better_results_on_a_board_score_against_each_other_table() {
+		let tables = vec![
+			vec![[160, 0], [0, 100]],
+			vec![[100, 0], [0, 100]],
+			vec![[0, 90], [0, 250]],
+		];
+		// Out of 4: 2 + 1.5, 1 + 1.5 and 0 + 0, the first two tables tying on board 1
+		assert_eq!(matchpoints(&tables), [87.5, 62.5, 0.]);
+	}
+
+	#[test]
+	fn only_boards_played_at_every_table_count() {
+		let tables = vec![vec![[100, 0], [0, 160]], vec![[0, 100]]];
+		assert_eq!(matchpoints(&tables), [100., 0.]);
+	}
+
+	#[test]
+	fn nothing_to_compare_is_even() {
+		assert_eq!(matchpoints(&[vec![[100, 0]]]), [50.]);
+		assert_eq!(matchpoints(&[vec![[100, 0]], Vec::new()]), [50., 50.]);
+		assert!(matchpoints(&[]).is_empty());
+	}
+
+	#[test]
+	fn swapped_tables_report_results_by_hands() {
+		let mut duplicate = Duplicate::new(0, true, 2);
+		duplicate.results.push([80, 0]);
+		assert_eq!(duplicate.results_by_hands(), [[0, 80]]);
+		assert_eq!(duplicate.dealer_id(0), 1);
+	}
+
+	#[test]
+	fn swapped_tables_deal_the_same_hands_one_seat_over() {
+		let straight = Duplicate::new(42, false, 1).deck(0).draw_n(32).unwrap();
+		let swapped = Duplicate::new(42, true, 1).deck(0).draw_n(32).unwrap();
+		assert_eq!(swapped[8..16], straight[..8]);
+		assert_eq!(swapped[..8], straight[24..]);
+	}
+}
@@ -0,0 +1,164 @@
+use crate::prelude::*;
+
+use std::fmt;
+
+pub type GameResult<T> = std::result::Result<T, GameError>;
+
+/// Why a client message was turned down
+///
+/// Serialized as a stable `code` (the variant name) along with its `params`, if any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(tag = "code", content = "params")]
+pub enum GameError {
+	/// The message couldn't be parsed
+	InvalidMessage {
+		details: String,
+	},
+	/// The client speaks another version of the protocol than the server's `protocol_version`
+	UnsupportedProtocol {
+		protocol_version: u32,
+	},
+	NotInitialized,
+	AlreadyInitialized,
+	/// No seat of the table goes with the token
+	InvalidSeatToken,
+	WrongPassword,
+	/// A guest already sits at the table under that name
+	UsernameTaken,
+	TableReserved,
+	TableFull,
+	WrongPhase {
+		expected: Phase,
+	},
+	Paused,
+	NotYourTurn,
+	BidTooLow {
+		previous: BidScore,
+	},
+	AlreadyCoinched,
+	NoOpponentBid,
+	NotCoinched,
+	NotCoinchedTeam,
+	InvalidCard,
+	IllegalCard {
+		reason: IllegalPlay,
+	},
+	ClaimPending,
+	UndoPending,
+	NoPendingClaim,
+	NotOpponent,
+	/// Opponents can still take a trick
+	ClaimRefuted,
+	NoPendingUndo,
+	NothingToUndo,
+	OwnRequest,
+	PredefinedDeals,
+	AlreadyPaused,
+	NotPaused,
+	NotHost,
+	MatchStarted,
+	SeatsReserved,
+	InvalidSeatOrder,
+	InvalidPlayer {
+		player_id: usize,
+	},
+	PlayerNotPresent {
+		player_id: usize,
+	},
+	PlayerPresent {
+		player_id: usize,
+	},
+	SeatVacant {
+		player_id: usize,
+	},
+	KickSelf,
+	EmptyMessage,
+	MessageTooLong {
+		max_len: usize,
+	},
+	TooManyMessages,
+	Silenced,
+	/// Internal failure, e.g. the game couldn't move on to the next phase
+	Internal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum Phase {
+	Bidding,
+	Running,
+}
+
+/// Which rule forbids playing a card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum IllegalPlay {
+	MustFollowSuit,
+	MustTrump,
+	/// A higher trump than the ones on the board is held
+	MustOvertrump,
+}
+
+impl fmt::Display for GameError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::InvalidMessage { details } => write!(f, "Invalid message: {}", details),
+			Self::UnsupportedProtocol { protocol_version } => write!(
+				f,
+				"Incompatible client, the server speaks protocol version {}: please reload",
+				protocol_version
+			),
+			Self::NotInitialized => write!(f, "Client not initialized"),
+			Self::AlreadyInitialized => write!(f, "Already initialized"),
+			Self::InvalidSeatToken => write!(f, "Invalid seat token"),
+			Self::WrongPassword => write!(f, "Wrong password"),
+			Self::UsernameTaken => write!(f, "Someone is already playing under that name"),
+			Self::TableReserved => write!(f, "This table is reserved"),
+			Self::TableFull => write!(f, "Game is full"),
+			Self::WrongPhase {
+				expected: Phase::Bidding,
+			} => write!(f, "Not in bidding phase"),
+			Self::WrongPhase {
+				expected: Phase::Running,
+			} => write!(f, "Games not in running state"),
+			Self::Paused => write!(f, "Game is paused"),
+			Self::NotYourTurn => write!(f, "Not your turn"),
+			Self::BidTooLow { .. } => write!(f, "New bid has to be greater than previous bid"),
+			Self::AlreadyCoinched => write!(f, "Already coinch-ed"),
+			Self::NoOpponentBid => write!(f, "No opponent's bid to 'coincher'"),
+			Self::NotCoinched => write!(f, "Game is in non-sur-coinchable state"),
+			Self::NotCoinchedTeam => write!(f, "Nothing to 'surcoincher'"),
+			Self::InvalidCard => write!(f, "Invalid card pos"),
+			Self::IllegalCard { reason } => match reason {
+				IllegalPlay::MustFollowSuit => write!(f, "You have to follow suit"),
+				IllegalPlay::MustTrump => write!(f, "You have to trump"),
+				IllegalPlay::MustOvertrump => write!(f, "You have to play a higher trump"),
+			},
+			Self::ClaimPending => write!(f, "A claim is pending"),
+			Self::UndoPending => write!(f, "A take-back request is pending"),
+			Self::NoPendingClaim => write!(f, "No pending claim"),
+			Self::NotOpponent => write!(f, "Only opponents can answer a claim"),
+			Self::ClaimRefuted => write!(f, "Opponents can still take a trick"),
+			Self::NoPendingUndo => write!(f, "No pending take-back request"),
+			Self::NothingToUndo => write!(f, "No card of yours to take back"),
+			Self::OwnRequest => write!(f, "Can't answer your own request"),
+			Self::PredefinedDeals => write!(f, "Deals are predefined at this table"),
+			Self::AlreadyPaused => write!(f, "Game is already paused"),
+			Self::NotPaused => write!(f, "Game is not paused"),
+			Self::NotHost => write!(f, "Only the host can do that"),
+			Self::MatchStarted => write!(f, "Not possible once the match started"),
+			Self::SeatsReserved => write!(f, "Seats are reserved at this table"),
+			Self::InvalidSeatOrder => write!(f, "Seat order should contain each player once"),
+			Self::InvalidPlayer { .. } => write!(f, "Invalid player"),
+			Self::PlayerNotPresent { .. } => write!(f, "Player is not present"),
+			Self::PlayerPresent { .. } => write!(f, "Can only kick absent players"),
+			Self::SeatVacant { .. } => write!(f, "Seat is already free"),
+			Self::KickSelf => write!(f, "Leave the table instead"),
+			Self::EmptyMessage => write!(f, "Empty message"),
+			Self::MessageTooLong { max_len } => write!(f, "Message is too long ({} characters max)", max_len),
+			Self::TooManyMessages => write!(f, "You are sending messages too fast"),
+			Self::Silenced => write!(f, "You have been silenced by the host"),
+			Self::Internal => write!(f, "Internal error"),
+		}
+	}
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,99 @@
+use crate::prelude::*;
+
+use {crate::server::websocket::SetPlayerId, serde::Serializer};
+
+impl Game {
+	pub fn check_host(&self, player_id: usize) -> GameResult<()> {
+		match self.host_id == Some(player_id) {
+			true => Ok(()),
+			false => Err(GameError::NotHost),
+		}
+	}
+
+	/// Whether a card has been played in this match
+	pub fn match_started(&self) -> bool {
+		!self.round_points.is_empty() || matches!(self.game_state, GameState::Running(_))
+	}
+
+	pub fn set_settings(&mut self, settings: Settings) -> GameResult<()> {
+		if self.match_started() {
+			return Err(GameError::MatchStarted);
+		}
+		self.settings = settings;
+		self.send_game_state_all();
+		Ok(())
+	}
+
+	pub fn set_password(&mut self, password: Option<String>) {
+		self.password = password;
+		self.send_game_state_all();
+	}
+
+	/// New seat `i` is given to the player who was seated at `order[i]`
+	pub fn reorder_seats(&mut self, order: &[usize]) -> GameResult<()> {
+		if self.match_started() {
+			return Err(GameError::MatchStarted);
+		}
+		if self.seat_reservations.is_some() {
+			return Err(GameError::SeatsReserved);
+		}
+		let mut sorted = order.to_vec();
+		sorted.sort_unstable();
+		if !sorted.into_iter().eq(self.player_ids()) {
+			return Err(GameError::InvalidSeatOrder);
+		}
+		self.permute_seats(order);
+		// Hands were dealt to the former seats
+		if !self.redeal() {
+			self.send_refresh_all_all();
+		}
+		Ok(())
+	}
+
+	/// Moves players without checks nor redeal, `order` being a permutation of the player ids
+	pub fn permute_seats(&mut self, order: &[usize]) {
+		let mut players: Vec<Option<Player>> = self.players.drain(..).map(Some).collect();
+		self.players = order.iter().map(|&id| players[id].take().unwrap()).collect();
+		self.host_id = self
+			.host_id
+			.and_then(|host_id| order.iter().position(|&id| id == host_id));
+		for (player_id, player) in self.players.iter_mut().enumerate() {
+			player.kick_votes.clear();
+			if let Some(web_socket) = &player.web_socket {
+				web_socket.do_send(SetPlayerId(Some(player_id)));
+			}
+		}
+	}
+
+	/// Starts a new match with the same players
+	pub fn restart(&mut self) {
+		self.points = [0, 0];
+		self.round_points.clear();
+		for player in self.players.iter_mut() {
+			player.cards.clear();
+		}
+		self.game_state = GameState::Lobby;
+		if self.pause.take().is_some() {
+			self.send_all(ServerMessage::Pause { pause: None });
+		}
+		if !self.try_bidding_phase() {
+			self.send_refresh_all_all();
+		}
+	}
+
+	pub fn transfer_host(&mut self, player_id: usize) -> GameResult<()> {
+		match self.players.get(player_id).map(|p| p.control) {
+			Some(Control::Human) => {
+				self.host_id = Some(player_id);
+				self.send_all(ServerMessage::Host { player_id });
+				Ok(())
+			}
+			_ => Err(GameError::PlayerNotPresent { player_id }),
+		}
+	}
+}
+
+/// Lets clients know whether the table is locked without leaking the password
+pub fn serialize_locked<S: Serializer>(password: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_bool(password.is_some())
+}
@@ -1,8 +1,30 @@
+pub mod bot;
 pub mod cards;
+pub mod chat;
+pub mod claim;
 pub mod contract;
+pub mod duplicate;
+pub mod error;
+pub mod host;
+pub mod pause;
 pub mod points;
+pub mod reaction;
+pub mod redeal;
+pub mod seats;
+pub mod settings;
+pub mod stats;
+pub mod turn;
+pub mod undo;
+pub mod view;
 
-use crate::prelude::*;
+use crate::{accounts::Accounts, notifier::TurnNotifier, prelude::*, tournament::TournamentTable};
+
+use {
+	actix::SpawnHandle,
+	std::{cmp::Ordering, sync::Arc},
+};
+
+const MAX_MISDEALS: usize = 100;
 
 #[derive(Debug, Serialize)]
 pub struct Game {
@@ -10,10 +32,43 @@ pub struct Game {
 	pub points: [usize; 2],
 	pub round_points: Vec<RoundPoints>,
 	pub dealer_id: usize,
+	pub deal_count: usize,
 	pub game_state: GameState,
+	pub settings: Settings,
+	pub host_id: Option<usize>,
+	#[serde(rename = "locked", serialize_with = "host::serialize_locked")]
+	pub password: Option<String>,
+	pub pause: Option<Pause>,
+	/// Players who asked for the cards to be dealt again
+	pub redeal_votes: Vec<usize>,
+	#[serde(skip)]
+	pub chat_history: Vec<ChatMessage>,
+	pub turn_deadline: Option<TurnDeadline>,
+	#[serde(skip)]
+	pub turn_timer: Option<SpawnHandle>,
+	/// Step at which a bot action is already scheduled
+	#[serde(skip)]
+	pub bot_step: Option<(usize, usize)>,
+	/// Told whose turn it is
+	#[serde(skip)]
+	pub notifier: Option<Arc<dyn TurnNotifier>>,
+	/// Step at which players were last notified of their turn
+	#[serde(skip)]
+	pub notified_step: Option<(usize, usize)>,
+	/// Where players' stats are recorded
+	#[serde(skip)]
+	pub accounts: Addr<Accounts>,
+	/// Players allowed at the table, in seat order
+	pub seat_reservations: Option<[SeatReservation; 4]>,
+	/// Where the table is reached, `None` for the main one
+	pub table_id: Option<String>,
+	#[serde(skip)]
+	pub tournament_table: Option<TournamentTable>,
+	/// Predefined deals, also played at other tables
+	pub duplicate: Option<Duplicate>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct RoundPoints {
 	pub points: [usize; 2],
 	pub bid: Bid,
@@ -34,27 +89,34 @@ pub enum GameState {
 #[derive(Debug, Serialize)]
 pub struct RunningGame {
 	pub team: bool,
+	/// Player who won the bidding
+	pub bidder_id: usize,
 	pub bid: Bid,
 	pub tricks: Vec<Trick>,
 	pub coinche_state: CoincheState,
 	pub board: Board,
 	pub belote_player: Option<usize>,
+	/// Play is suspended until opponents answer
+	pub claim: Option<Claim>,
+	pub last_play: Option<LastPlay>,
+	/// Play is suspended until the other players answer
+	pub undo: Option<UndoRequest>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Board {
 	pub starting_player_id: usize,
 	pub cards: Vec<Card>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Trick {
 	pub starting_player_id: usize,
 	pub winner_id: usize,
 	pub cards: Vec<Card>,
 }
 
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, JsonSchema)]
 pub enum CoincheState {
 	No,
 	Coinche { player_id: usize },
@@ -74,14 +136,49 @@ pub enum BiddingCoincheState {
 	},
 }
 
+/// Who a seat of a reserved table is held for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SeatReservation {
+	/// Only a player logged in to the account gets the seat
+	Account(String),
+	/// Any guest going by that name gets the seat
+	Guest(String),
+}
+
+impl SeatReservation {
+	pub fn is_for(&self, player: &Player) -> bool {
+		match self {
+			Self::Account(username) => player.account.as_ref() == Some(username),
+			Self::Guest(name) => player.account.is_none() && &player.username == name,
+		}
+	}
+}
+
 impl Game {
-	pub fn new() -> Self {
+	pub fn new(settings: Settings, accounts: Addr<Accounts>) -> Self {
 		Self {
 			players: Vec::new(),
 			points: [0, 0],
 			round_points: Vec::new(),
 			dealer_id: 2,
+			deal_count: 0,
 			game_state: GameState::Lobby,
+			settings,
+			host_id: None,
+			password: None,
+			pause: None,
+			redeal_votes: Vec::new(),
+			chat_history: Vec::new(),
+			turn_deadline: None,
+			turn_timer: None,
+			bot_step: None,
+			notifier: None,
+			notified_step: None,
+			accounts,
+			seat_reservations: None,
+			table_id: None,
+			tournament_table: None,
+			duplicate: None,
 		}
 	}
 
@@ -89,51 +186,166 @@ impl Game {
 		0..self.players.len()
 	}
 
-	pub fn players<'a>(&'a self) -> impl Iterator<Item = PlayerPtr<&'a Game>> {
+	pub fn players(&self) -> impl Iterator<Item = PlayerPtr<&Game>> {
 		self.player_ids().map(move |i| self.player(i))
 	}
 
-	pub fn player<'a>(&'a self, player_id: usize) -> PlayerPtr<&'a Game> {
+	pub fn player(&self, player_id: usize) -> PlayerPtr<&Game> {
 		PlayerPtr { game: self, player_id }
 	}
 
-	pub fn player_mut<'a>(&'a mut self, player_id: usize) -> PlayerPtr<&'a mut Game> {
+	pub fn player_mut(&mut self, player_id: usize) -> PlayerPtr<&mut Game> {
 		PlayerPtr { game: self, player_id }
 	}
 
-	pub fn add_player(&mut self, player: Player) -> crate::Result<usize> {
-		// Try find user again
-		if let Some(id) = self.players.iter().position(|p| p.username == player.username) {
+	/// A returning player gets the messages sent since `last_seq` if available, the whole state otherwise
+	pub fn add_player(
+		&mut self,
+		player: Player,
+		password: Option<String>,
+		seat_token: Option<String>,
+		last_seq: Option<u64>,
+	) -> GameResult<usize> {
+		// Try find user again, by account if logged in so that nobody else can take their seat
+		if let Some(id) = self.players.iter().position(|p| {
+			p.control != Control::Vacant
+				&& p.account == player.account
+				&& (player.account.is_some() || p.username == player.username)
+		}) {
+			// Anybody can use a guest's name: only the client that was given the seat gets it back
+			if player.account.is_none() && seat_token.as_deref() != Some(self.players[id].token.as_str()) {
+				return Err(GameError::UsernameTaken);
+			}
 			self.players[id].web_socket = player.web_socket;
-			let player = self.player(id);
-			player.send_refresh_all()?;
+			// Whoever held the seat before can't act on its behalf anymore
+			self.players[id].token = player.token;
+			self.players[id]
+				.outbox
+				.get_mut()
+				.set_encoding(player.outbox.borrow().encoding());
+			self.players[id].kick_votes.clear();
+			self.players[id].send_welcome();
+			let replayed = last_seq.is_some_and(|last_seq| self.players[id].replay(last_seq));
+			self.set_control(id, Control::Human);
+			if !replayed {
+				let _ = self.player(id).send_refresh_all();
+			}
 			Ok(id)
 		} else {
-			if self.players.len() >= 4 {
-				Err(err_msg("Game is full"))
+			if self.password.is_some() && password != self.password {
+				return Err(GameError::WrongPassword);
+			}
+			let vacant_seat = match &self.seat_reservations {
+				Some(reservations) => {
+					let reservation = match reservations.iter().find(|r| r.is_for(&player)) {
+						Some(reservation) => reservation,
+						None => return Err(GameError::TableReserved),
+					};
+					// Reserved seats can only be taken back by their player
+					self.players
+						.iter()
+						.position(|p| p.control == Control::Vacant && reservation.is_for(p))
+				}
+				None => self.players.iter().position(|p| p.control == Control::Vacant),
+			};
+			let mut id = if let Some(id) = vacant_seat {
+				// Take over the seat along with its hand
+				let seat = &mut self.players[id];
+				seat.username = player.username;
+				seat.account = player.account;
+				seat.web_socket = player.web_socket;
+				seat.token = player.token;
+				seat.outbox.get_mut().set_encoding(player.outbox.borrow().encoding());
+				seat.timeouts = 0;
+				self.set_control(id, Control::Human);
+				id
+			} else if self.players.len() >= 4 {
+				return Err(GameError::TableFull);
 			} else {
-				let id = self.players.len();
 				self.players.push(player);
-				if !self.try_bidding_phase() {
-					let player = self.player(id);
-					player.send_refresh_all()?;
-					for other_player in self.players() {
-						if other_player.player_id != player.player_id {
-							let _ = other_player.send_game_state();
-						}
+				self.players.len() - 1
+			};
+			self.players[id].send_welcome();
+			if let (Some(reservations), 4) = (&self.seat_reservations, self.players.len()) {
+				if !self.match_started() {
+					// Players joined in any order: move them to their seats
+					let order: Vec<usize> = reservations
+						.iter()
+						.map(|r| self.players.iter().position(|p| r.is_for(p)).unwrap())
+						.collect();
+					self.permute_seats(&order);
+					id = order.iter().position(|&i| i == id).unwrap();
+				}
+			}
+			// Reserved tables are run by the server
+			if self.host_id.is_none() && self.seat_reservations.is_none() {
+				self.host_id = Some(id);
+			}
+			if !self.try_bidding_phase() {
+				let player = self.player(id);
+				let _ = player.send_refresh_all();
+				for other_player in self.players() {
+					if other_player.player_id != player.player_id {
+						let _ = other_player.send_game_state();
 					}
 				}
-				Ok(id)
 			}
+			Ok(id)
 		}
 	}
 
 	pub fn try_bidding_phase(&mut self) -> bool {
-		if self.game_state.is_lobby() && self.players.len() == 4 {
+		if self.tournament_table.as_ref().is_some_and(|t| t.finished) {
+			return false;
+		}
+		// Once the match has started, vacant seats are played by the bot
+		if self.game_state.is_lobby()
+			&& self.players.len() == 4
+			&& (self.deal_count > 0 || self.players.iter().all(|p| p.control != Control::Vacant))
+		{
 			self.dealer_id = (self.dealer_id + 1) % 4;
-			let mut deck = Deck::new_shuffled();
-			for player in self.players.iter_mut() {
-				player.cards = deck.draw_n(32 / 4).unwrap();
+			self.deal_count += 1;
+			self.redeal_votes.clear();
+			if let Some(duplicate) = &self.duplicate {
+				// Same hands as at the other tables, whatever the misdeal rules
+				let board = duplicate.board();
+				let mut deck = duplicate.deck(board);
+				for player in self.players.iter_mut() {
+					player.cards = deck.draw_n(32 / 4).unwrap();
+				}
+				self.dealer_id = duplicate.dealer_id(board);
+			} else {
+				// Give up on misdeals eventually in case the rules are too strict
+				let mut first_misdeal = None;
+				let mut waived = None;
+				for attempt in 1..=MAX_MISDEALS {
+					let mut deck = Deck::new_shuffled();
+					for player in self.players.iter_mut() {
+						player.cards = deck.draw_n(32 / 4).unwrap();
+					}
+					match self.find_misdeal() {
+						Some(misdeal) if attempt == MAX_MISDEALS => waived = Some(misdeal),
+						Some(misdeal) => {
+							first_misdeal.get_or_insert(misdeal);
+						}
+						None => break,
+					}
+				}
+				// Told once, whatever the number of times the cards were shuffled again
+				if let Some((player_id, misdeal)) = waived {
+					warn!("Misdeal rule {:?} waived after {} deals", misdeal, MAX_MISDEALS);
+					self.send_all(ServerMessage::Misdeal {
+						player_id,
+						misdeal,
+						waived: true,
+					});
+				} else if let Some((player_id, misdeal)) = first_misdeal {
+					self.send_all(ServerMessage::Misdeal {
+						player_id,
+						misdeal,
+						waived: false,
+					});
+				}
 			}
 			self.game_state = GameState::Bidding {
 				bids: Vec::new(),
@@ -146,6 +358,18 @@ impl Game {
 		}
 	}
 
+	/// Deals new hands, keeping the same dealer
+	pub fn redeal(&mut self) -> bool {
+		match self.game_state {
+			GameState::Bidding { .. } => {
+				self.game_state = GameState::Lobby;
+				self.dealer_id = (self.dealer_id + 3) % 4;
+				self.try_bidding_phase()
+			}
+			_ => false,
+		}
+	}
+
 	pub fn try_playing_phase(&mut self) -> bool {
 		match &self.game_state {
 			GameState::Bidding { bids, coinche_state } => {
@@ -166,11 +390,19 @@ impl Game {
 				match player_bid.bid {
 					None => {
 						self.game_state = GameState::Lobby;
+						if let Some(duplicate) = &mut self.duplicate {
+							duplicate.results.push([0, 0]);
+						}
+						if self.try_end_match() {
+							self.restart();
+							return true;
+						}
 						self.try_bidding_phase()
 					}
 					Some(bid) => {
 						self.game_state = GameState::Running(RunningGame {
 							team: Player::team(player_bid.player_id),
+							bidder_id: player_bid.player_id,
 							bid,
 							board: Board {
 								starting_player_id: (self.dealer_id + 1) % 4,
@@ -189,6 +421,9 @@ impl Game {
 							},
 							tricks: Vec::new(),
 							belote_player: None,
+							claim: None,
+							last_play: None,
+							undo: None,
 						});
 						self.send_game_state_all();
 						true
@@ -233,12 +468,20 @@ impl Game {
 				round_points[!running.team as usize] = def_points;
 				self.points[0] += round_points[0];
 				self.points[1] += round_points[1];
+				self.record_deal(running, round_points, capot);
+				if let Some(duplicate) = &mut self.duplicate {
+					duplicate.results.push(round_points);
+				}
 				self.round_points.push(RoundPoints {
 					team: running.team,
 					bid: running.bid,
 					points: round_points,
 					scored_points,
 				});
+				if self.try_end_match() {
+					self.restart();
+					return;
+				}
 				self.game_state = GameState::Lobby;
 				if !self.try_bidding_phase() {
 					self.send_refresh_all_all();
@@ -247,6 +490,13 @@ impl Game {
 		}
 	}
 
+	pub fn set_control(&mut self, player_id: usize, control: Control) {
+		if self.players[player_id].control != control {
+			self.players[player_id].control = control;
+			self.send_all(ServerMessage::Control { player_id, control });
+		}
+	}
+
 	pub fn send_refresh_all_all(&self) {
 		for player in self.players() {
 			let _ = player.send_refresh_all();
@@ -267,15 +517,93 @@ impl Game {
 }
 
 impl GameState {
-	fn is_lobby(&self) -> bool {
-		match self {
-			Self::Lobby => true,
-			_ => false,
-		}
+	pub fn is_lobby(&self) -> bool {
+		matches!(self, Self::Lobby)
+	}
+}
+
+impl RunningGame {
+	/// Whether the rules allow a player holding `cards` to play `try_play_card` on the current board
+	pub fn can_play(&self, cards: &[Card], try_play_card: Card, team: bool) -> bool {
+		self.board.can_play(self.bid.trump, cards, try_play_card, team)
+	}
+
+	pub fn check_play(&self, cards: &[Card], try_play_card: Card, team: bool) -> Result<(), IllegalPlay> {
+		self.board.check_play(self.bid.trump, cards, try_play_card, team)
 	}
 }
 
 impl Board {
+	pub fn can_play(&self, trump: Trump, cards: &[Card], try_play_card: Card, team: bool) -> bool {
+		self.check_play(trump, cards, try_play_card, team).is_ok()
+	}
+
+	/// Which rule the card would break, if any
+	pub fn check_play(&self, trump: Trump, cards: &[Card], try_play_card: Card, team: bool) -> Result<(), IllegalPlay> {
+		if let Some(asked_suit) = self.cards.first().map(|c| c.suit) {
+			if cards.iter().any(|c| c.suit == asked_suit) {
+				// Forced to play the asked suit
+				if try_play_card.suit != asked_suit {
+					Err(IllegalPlay::MustFollowSuit)
+				} else {
+					// We're the right suit. But right number?
+					if trump.is_trump(asked_suit) {
+						// Forced to play higher if possible
+						let high_trump_value = self.high_trump_value(asked_suit).unwrap();
+						match high_trump_value.cmp_trump(&try_play_card.value) == Ordering::Less
+							|| cards
+								.iter()
+								.filter(|c| c.suit == asked_suit)
+								.all(|c| c.value.cmp_trump(&high_trump_value) == Ordering::Less)
+						{
+							true => Ok(()),
+							false => Err(IllegalPlay::MustOvertrump),
+						}
+					} else {
+						Ok(())
+					}
+				}
+			} else {
+				let should_play_trump: Option<Suit> = match trump {
+					Trump::Suit(trump_suit) => {
+						if Player::team(self.winning_player_id(trump).unwrap()) != team
+							&& cards.iter().any(|c| c.suit == trump_suit)
+						{
+							Some(trump_suit)
+						} else {
+							None
+						}
+					}
+					_ => None,
+				};
+				if let Some(trump_suit) = should_play_trump {
+					if try_play_card.suit != trump_suit {
+						Err(IllegalPlay::MustTrump)
+					} else {
+						// We're the right suit (trump). But right number ?
+						if let Some(high_trump_value) = self.high_trump_value(trump_suit) {
+							match high_trump_value.cmp_trump(&try_play_card.value) == Ordering::Less
+								|| cards
+									.iter()
+									.filter(|c| c.suit == trump_suit)
+									.all(|c| c.value.cmp_trump(&high_trump_value) == Ordering::Less)
+							{
+								true => Ok(()),
+								false => Err(IllegalPlay::MustOvertrump),
+							}
+						} else {
+							Ok(())
+						}
+					}
+				} else {
+					Ok(())
+				}
+			}
+		} else {
+			Ok(())
+		}
+	}
+
 	pub fn high_trump_value(&self, asked_suit: Suit) -> Option<Value> {
 		self.suit_values(asked_suit).max_by(Value::cmp_trump)
 	}
@@ -306,8 +634,73 @@ impl Board {
 	}
 }
 
-#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
 pub enum BeloteRebelote {
 	Belote,
 	Rebelote,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn cards(cards: &str) -> Vec<Card> {
+		cards.split_whitespace().map(|card| card.parse().unwrap()).collect()
+	}
+
+	/// Whether the next player may play `card` out of `hand`, Hearts being trump
+	fn check_play(starting_player_id: usize, board: &str, hand: &str, card: &str) -> Result<(), IllegalPlay> {
+		let board = Board {
+			starting_player_id,
+			cards: cards(board),
+		};
+		let player_id = (starting_player_id + board.cards.len()) % 4;
+		board.check_play(
+			Trump::Suit(Suit::Hearts),
+			&cards(hand),
+			card.parse().unwrap(),
+			Player::team(player_id),
+		)
+	}
+
+	#[test]
+	fn any_card_leads() {
+		assert_eq!(check_play(0, "", "7s Jh", "7s"), Ok(()));
+		assert_eq!(check_play(0, "", "7s Jh", "Jh"), Ok(()));
+	}
+
+	#[test]
+	fn asked_suit_must_be_followed() {
+		assert_eq!(check_play(0, "7s", "8s Kc 7h", "Kc"), Err(IllegalPlay::MustFollowSuit));
+		assert_eq!(check_play(0, "7s", "8s Kc 7h", "7h"), Err(IllegalPlay::MustFollowSuit));
+		assert_eq!(check_play(0, "As", "8s Kc", "8s"), Ok(()));
+	}
+
+	#[test]
+	fn opponents_winning_the_trick_must_be_trumped() {
+		assert_eq!(check_play(0, "As", "Kc 7h", "Kc"), Err(IllegalPlay::MustTrump));
+		assert_eq!(check_play(0, "As", "Kc 7h", "7h"), Ok(()));
+		// Without trumps, anything goes
+		assert_eq!(check_play(0, "As", "Kc 7d", "7d"), Ok(()));
+	}
+
+	#[test]
+	fn partner_winning_the_trick_needs_no_trump() {
+		assert_eq!(check_play(0, "As 7s", "Kc 7h", "Kc"), Ok(()));
+	}
+
+	#[test]
+	fn trumps_must_go_higher_when_possible() {
+		// Following trump
+		assert_eq!(check_play(0, "9h", "7h Jh", "7h"), Err(IllegalPlay::MustOvertrump));
+		assert_eq!(check_play(0, "9h", "7h Jh", "Jh"), Ok(()));
+		assert_eq!(check_play(0, "9h", "7h 8h", "7h"), Ok(()));
+		// Trumping over an opponent's trump
+		assert_eq!(
+			check_play(0, "As 9h", "Kc 7h Jh", "7h"),
+			Err(IllegalPlay::MustOvertrump)
+		);
+		assert_eq!(check_play(0, "As 9h", "Kc 7h Jh", "Jh"), Ok(()));
+		assert_eq!(check_play(0, "As 9h", "Kc 7h 8h", "7h"), Ok(()));
+	}
+}
@@ -0,0 +1,84 @@
+use crate::prelude::*;
+
+use std::time::Duration;
+
+/// Layered over the game state: gameplay actions and timers are frozen while paused
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Pause {
+	pub player_id: usize,
+	/// Players who agreed to resume so far
+	pub resume_votes: Vec<usize>,
+	/// What was left on the turn clock, handed back on resume
+	#[serde(skip)]
+	pub turn_time_left: Option<((usize, usize), Duration)>,
+}
+
+impl Game {
+	pub fn pause(&mut self, player_id: usize) -> GameResult<()> {
+		if self.pause.is_some() {
+			return Err(GameError::AlreadyPaused);
+		}
+		if self.game_state.is_lobby() {
+			return Err(GameError::WrongPhase {
+				expected: Phase::Running,
+			});
+		}
+		let turn_time_left = self.turn_deadline.map(|d| {
+			let left_ms = d.deadline - chrono::Utc::now().timestamp_millis();
+			(d.step, Duration::from_millis(left_ms.max(0) as u64))
+		});
+		self.pause = Some(Pause {
+			player_id,
+			resume_votes: Vec::new(),
+			turn_time_left,
+		});
+		self.send_all(ServerMessage::Pause {
+			pause: self.pause.as_ref(),
+		});
+		Ok(())
+	}
+
+	/// Play resumes once every player present agreed, or right away if asked by the host
+	pub fn vote_resume(&mut self, player_id: usize) -> GameResult<()> {
+		let pause = self.pause.as_mut().ok_or(GameError::NotPaused)?;
+		if !pause.resume_votes.contains(&player_id) {
+			pause.resume_votes.push(player_id);
+		}
+		let all_voted = self
+			.players
+			.iter()
+			.enumerate()
+			.filter(|(_, p)| p.control == Control::Human)
+			.all(|(id, _)| pause.resume_votes.contains(&id));
+		if all_voted || self.host_id == Some(player_id) {
+			self.resume();
+		} else {
+			self.send_all(ServerMessage::Pause {
+				pause: self.pause.as_ref(),
+			});
+		}
+		Ok(())
+	}
+
+	pub fn resume(&mut self) {
+		if let Some(pause) = self.pause.take() {
+			// Picked up by the turn timer, which was stopped while paused
+			self.turn_deadline = pause.turn_time_left.and_then(|(step, time_left)| {
+				let turn = self.current_turn()?;
+				(self.turn_step() == step).then(|| TurnDeadline {
+					turn,
+					deadline: (chrono::Utc::now() + chrono::Duration::from_std(time_left).unwrap()).timestamp_millis(),
+					step,
+				})
+			});
+			// Give the table a full window again
+			if let GameState::Running(RunningGame { undo: Some(undo), .. }) = &mut self.game_state {
+				undo.expires_at = (chrono::Utc::now()
+					+ chrono::Duration::seconds(self.settings.undo_window_secs as i64))
+				.timestamp_millis();
+				undo.expiry_scheduled = false;
+			}
+			self.send_all(ServerMessage::Pause { pause: None });
+		}
+	}
+}
@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+use std::cmp::Ordering;
+
 impl Card {
 	pub fn points(self, trump: Trump) -> f64 {
 		use {TrumpMatch::*, Value::*};
@@ -21,4 +23,12 @@ impl Card {
 			_ => base,
 		}
 	}
+
+	/// Orders cards from the cheapest to give away to the most valuable
+	pub fn cmp_points(self, other: Card, trump: Trump) -> Ordering {
+		self.points(trump)
+			.partial_cmp(&other.points(trump))
+			.unwrap()
+			.then_with(|| self.value.cmp(&other.value))
+	}
 }
@@ -0,0 +1,46 @@
+use crate::prelude::*;
+
+use std::time::{Duration, Instant};
+
+/// Minimum delay between two reactions of the same player
+const REACTION_COOLDOWN: Duration = Duration::from_secs(3);
+
+/// Predefined reactions, shown as a transient bubble next to the seat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Reaction {
+	/// "Bien joué"
+	WellPlayed,
+	/// "Oups"
+	Oops,
+	/// "Merci"
+	Thanks,
+	/// "Vite !"
+	Hurry,
+	Laugh,
+	Angry,
+	ThumbsUp,
+}
+
+impl Game {
+	pub fn react(&mut self, player_id: usize, reaction: Reaction) -> GameResult<()> {
+		let player = &mut self.players[player_id];
+		if player.silenced {
+			return Err(GameError::Silenced);
+		}
+		let now = Instant::now();
+		if player
+			.last_reaction
+			.is_some_and(|t| now.duration_since(t) < REACTION_COOLDOWN)
+		{
+			// Dropped silently, there's no point in bothering the player about it
+			return Ok(());
+		}
+		player.last_reaction = Some(now);
+		for player in self.players.iter() {
+			if !player.muted.contains(&player_id) {
+				let _ = player.send(ServerMessage::Reaction { player_id, reaction });
+			}
+		}
+		Ok(())
+	}
+}
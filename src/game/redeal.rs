@@ -0,0 +1,138 @@
+use crate::prelude::*;
+
+use std::str::FromStr;
+
+/// Hands that get the cards dealt again automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Misdeal {
+	/// No jack, queen nor king
+	NoFaceCard,
+	NoAce,
+}
+
+impl Misdeal {
+	pub fn matches(self, cards: &[Card]) -> bool {
+		match self {
+			Self::NoFaceCard => !cards
+				.iter()
+				.any(|c| matches!(c.value, Value::Jack | Value::Queen | Value::King)),
+			Self::NoAce => !cards.iter().any(|c| c.value == Value::Ace),
+		}
+	}
+}
+
+impl FromStr for Misdeal {
+	type Err = failure::Error;
+	fn from_str(s: &str) -> crate::Result<Self> {
+		match s {
+			"NoFaceCard" => Ok(Self::NoFaceCard),
+			"NoAce" => Ok(Self::NoAce),
+			_ => Err(err_msg("Unknown misdeal")),
+		}
+	}
+}
+
+impl Game {
+	/// First player whose hand is a misdeal according to the table rules
+	pub fn find_misdeal(&self) -> Option<(usize, Misdeal)> {
+		self.players.iter().enumerate().find_map(|(player_id, player)| {
+			let misdeal = self.settings.misdeals.iter().find(|m| m.matches(&player.cards))?;
+			Some((player_id, *misdeal))
+		})
+	}
+
+	/// Cards are dealt again with the same dealer once every player present asked for it
+	pub fn request_redeal(&mut self, player_id: usize) -> GameResult<()> {
+		if !matches!(self.game_state, GameState::Bidding { .. }) {
+			return Err(GameError::WrongPhase {
+				expected: Phase::Bidding,
+			});
+		}
+		if self.duplicate.is_some() {
+			return Err(GameError::PredefinedDeals);
+		}
+		if !self.redeal_votes.contains(&player_id) {
+			self.redeal_votes.push(player_id);
+		}
+		let all_voted = self
+			.players
+			.iter()
+			.enumerate()
+			.filter(|(_, p)| p.control == Control::Human)
+			.all(|(id, _)| self.redeal_votes.contains(&id));
+		if !all_voted || !self.redeal() {
+			self.send_all(ServerMessage::RedealVotes {
+				votes: &self.redeal_votes,
+			});
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::*,
+		crate::{accounts::Accounts, messages::EncodedMessage},
+		actix::Actor,
+	};
+
+	fn cards(hand: &str) -> Vec<Card> {
+		hand.split_whitespace().map(|card| card.parse().unwrap()).collect()
+	}
+
+	#[test]
+	fn no_face_card() {
+		assert!(Misdeal::NoFaceCard.matches(&cards("7s 8s 9h Th Ah 7d 8c 9c")));
+		assert!(!Misdeal::NoFaceCard.matches(&cards("7s 8s 9h Th Ah 7d 8c Jc")));
+		assert!(!Misdeal::NoFaceCard.matches(&cards("7s 8s 9h Th Ah 7d 8c Qc")));
+		assert!(!Misdeal::NoFaceCard.matches(&cards("7s 8s 9h Th Ah 7d 8c Kc")));
+	}
+
+	#[test]
+	fn no_ace() {
+		assert!(Misdeal::NoAce.matches(&cards("7s Js Qh Th Kh 7d 8c 9c")));
+		assert!(!Misdeal::NoAce.matches(&cards("7s Js Qh Th Kh 7d 8c Ac")));
+	}
+
+	#[test]
+	fn parses_setting_names() {
+		assert_eq!("NoAce".parse::<Misdeal>().unwrap(), Misdeal::NoAce);
+		assert_eq!("NoFaceCard".parse::<Misdeal>().unwrap(), Misdeal::NoFaceCard);
+		assert!("NoTen".parse::<Misdeal>().is_err());
+	}
+
+	#[actix_web::test]
+	async fn misdeals_are_announced_once_per_deal() {
+		let accounts_path = std::env::temp_dir().join(format!("coinche-redeal-test-{}.json", std::process::id()));
+		let accounts = Accounts::load(accounts_path).unwrap().start();
+		let settings = Settings {
+			misdeals: vec![Misdeal::NoFaceCard, Misdeal::NoAce],
+			..Settings::default()
+		};
+		let mut game = Game::new(settings, accounts);
+		for username in ["a", "b", "c", "d"] {
+			game.players
+				.push(Player::new(username.to_owned(), None, None, Encoding::Json));
+		}
+		const DEALS: usize = 30;
+		assert!(game.try_bidding_phase());
+		for _ in 1..DEALS {
+			assert!(game.find_misdeal().is_none());
+			assert!(game.redeal());
+		}
+		let misdeal_messages = game.players[0]
+			.outbox
+			.borrow()
+			.since(0)
+			.unwrap()
+			.filter(|encoded| matches!(encoded, EncodedMessage::Text(json) if json.starts_with("{\"Misdeal\"")))
+			.count();
+		// Some of the 4 hands lack an ace or face cards more often than not
+		assert!(
+			misdeal_messages > 0 && misdeal_messages <= DEALS,
+			"{}",
+			misdeal_messages
+		);
+	}
+}
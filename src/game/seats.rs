@@ -0,0 +1,53 @@
+use crate::prelude::*;
+
+impl Game {
+	/// Frees the seat for someone else to take over, keeping its hand and its team's points
+	pub fn vacate_seat(&mut self, player_id: usize) {
+		self.players[player_id].kick_votes.clear();
+		self.set_control(player_id, Control::Vacant);
+		let seat = &mut self.players[player_id];
+		seat.silenced = false;
+		seat.muted.clear();
+		for player in self.players.iter_mut() {
+			player.kick_votes.retain(|&voter_id| voter_id != player_id);
+			player.muted.retain(|&muted_id| muted_id != player_id);
+		}
+		if self.host_id == Some(player_id) {
+			self.host_id = self.players.iter().position(|p| p.control == Control::Human);
+			if let Some(host_id) = self.host_id {
+				self.send_all(ServerMessage::Host { player_id: host_id });
+			}
+		}
+	}
+
+	/// Kicks an absent player once every other player present voted for it
+	pub fn vote_kick(&mut self, voter_id: usize, player_id: usize) -> GameResult<()> {
+		if voter_id == player_id {
+			return Err(GameError::KickSelf);
+		}
+		match self.players.get(player_id).map(|p| p.control) {
+			None | Some(Control::Vacant) => return Err(GameError::SeatVacant { player_id }),
+			Some(Control::Human) => return Err(GameError::PlayerPresent { player_id }),
+			Some(Control::Disconnected) | Some(Control::Bot) => (),
+		}
+		let votes = &mut self.players[player_id].kick_votes;
+		if !votes.contains(&voter_id) {
+			votes.push(voter_id);
+		}
+		let all_voted = self
+			.players
+			.iter()
+			.enumerate()
+			.filter(|&(id, p)| id != player_id && p.control == Control::Human)
+			.all(|(id, _)| self.players[player_id].kick_votes.contains(&id));
+		if all_voted {
+			self.vacate_seat(player_id);
+		} else {
+			self.send_all(ServerMessage::KickVotes {
+				player_id,
+				votes: &self.players[player_id].kick_votes,
+			});
+		}
+		Ok(())
+	}
+}
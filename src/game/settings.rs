@@ -0,0 +1,77 @@
+use crate::prelude::*;
+
+use std::{env, time::Duration};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Settings {
+	/// Seconds a player has to bid (or surcoincher) before the server passes for them
+	pub bid_timeout_secs: Option<u64>,
+	/// Seconds a player has to play before the server plays their lowest legal card
+	pub play_timeout_secs: Option<u64>,
+	/// Seconds a disconnected player's seat is held before a bot takes over, `None` to wait for them forever
+	pub disconnect_grace_secs: Option<u64>,
+	/// Hands for which cards are dealt again
+	pub misdeals: Vec<Misdeal>,
+	/// Seconds the other players have to accept taking back a card
+	pub undo_window_secs: u64,
+	/// Words masked in chat messages
+	pub chat_word_filter: Vec<String>,
+	/// Points to reach for winning the match, scores are kept going forever if unset
+	pub match_points: Option<usize>,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			bid_timeout_secs: None,
+			play_timeout_secs: None,
+			disconnect_grace_secs: Some(60),
+			misdeals: Vec::new(),
+			undo_window_secs: 10,
+			chat_word_filter: Vec::new(),
+			match_points: None,
+		}
+	}
+}
+
+impl Settings {
+	/// Reads settings from env vars, leaving unset ones to their default
+	pub fn from_env() -> Self {
+		Self {
+			bid_timeout_secs: env_secs("BID_TIMEOUT_SECS"),
+			play_timeout_secs: env_secs("PLAY_TIMEOUT_SECS"),
+			// "none" keeps seats for disconnected players until they come back
+			disconnect_grace_secs: match env::var("DISCONNECT_GRACE_SECS").as_deref() {
+				Ok("none") => None,
+				_ => env_secs("DISCONNECT_GRACE_SECS").or(Self::default().disconnect_grace_secs),
+			},
+			misdeals: env::var("MISDEALS").ok().map_or_else(Vec::new, |s| {
+				s.split(',')
+					.map(|m| m.trim().parse().expect("Invalid misdeal in MISDEALS env var"))
+					.collect()
+			}),
+			undo_window_secs: env_secs("UNDO_WINDOW_SECS").unwrap_or(Self::default().undo_window_secs),
+			chat_word_filter: env::var("CHAT_WORD_FILTER")
+				.ok()
+				.map_or_else(Vec::new, |s| s.split(',').map(|w| w.trim().to_owned()).collect()),
+			match_points: env::var("MATCH_POINTS")
+				.ok()
+				.map(|s| s.parse().expect("Invalid MATCH_POINTS value in env var")),
+		}
+	}
+
+	pub fn turn_timeout(&self, turn: Turn) -> Option<Duration> {
+		match turn {
+			Turn::Bid { .. } | Turn::SurCoinche { .. } => self.bid_timeout_secs,
+			Turn::PlayCard { .. } => self.play_timeout_secs,
+		}
+		.map(Duration::from_secs)
+	}
+}
+
+fn env_secs(var: &str) -> Option<u64> {
+	env::var(var)
+		.ok()
+		.map(|s| s.parse().unwrap_or_else(|_| panic!("Invalid {} value in env var", var)))
+}
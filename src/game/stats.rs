@@ -0,0 +1,82 @@
+use crate::{
+	accounts::stats::{DealRecord, RecordDeal, RecordMatch},
+	prelude::*,
+	tournament::TableResult,
+};
+
+impl Game {
+	/// Sends what each logged in player did during the deal to their account's stats
+	pub fn record_deal(&self, running: &RunningGame, round_points: [usize; 2], capot: [bool; 2]) {
+		let made = round_points[running.team as usize] > 0;
+		let records = self
+			.players
+			.iter()
+			.enumerate()
+			.filter(|(_, p)| p.control != Control::Vacant)
+			.filter_map(|(player_id, player)| {
+				let team = Player::team(player_id);
+				let (coincher_id, surcoincher_id) = match running.coinche_state {
+					CoincheState::No => (None, None),
+					CoincheState::Coinche { player_id } => (Some(player_id), None),
+					CoincheState::Surcoinche {
+						coincher_id,
+						surcoincher_id,
+					} => (Some(coincher_id), Some(surcoincher_id)),
+				};
+				Some(DealRecord {
+					account: player.account.clone()?,
+					points: round_points[team as usize],
+					contract: (running.bidder_id == player_id).then_some((running.bid, made)),
+					coinche: (coincher_id == Some(player_id)).then_some(!made),
+					surcoinche: (surcoincher_id == Some(player_id)).then_some(made),
+					belote: running.belote_player == Some(player_id),
+					capot: capot[team as usize],
+				})
+			})
+			.collect::<Vec<_>>();
+		if !records.is_empty() {
+			self.accounts.do_send(RecordDeal(records));
+		}
+	}
+
+	/// Ends the match once a team reached the target, or all duplicate boards were played.
+	/// Returns whether it did.
+	pub fn try_end_match(&mut self) -> bool {
+		let winning_team = match (&self.duplicate, self.settings.match_points) {
+			(Some(duplicate), _) => match self.points {
+				_ if duplicate.results.len() < duplicate.boards => return false,
+				[p0, p1] if p0 == p1 => None,
+				[p0, p1] => Some(p1 > p0),
+			},
+			(None, Some(match_points)) => match self.points {
+				[p0, p1] if p0.max(p1) < match_points || p0 == p1 => return false,
+				[p0, p1] => Some(p1 > p0),
+			},
+			(None, None) => return false,
+		};
+		let mut seats: [Option<String>; 4] = Default::default();
+		for (seat, player) in seats.iter_mut().zip(self.players.iter()) {
+			if player.control != Control::Vacant {
+				seat.clone_from(&player.account);
+			}
+		}
+		if let (true, Some(winning_team)) = (seats.iter().any(Option::is_some), winning_team) {
+			self.accounts.do_send(RecordMatch { seats, winning_team });
+		}
+		if let Some(table) = &mut self.tournament_table {
+			table.finished = true;
+			table.tournaments.do_send(TableResult {
+				tournament_id: table.tournament_id,
+				round: table.round,
+				table: table.table,
+				points: self.points,
+				boards: self.duplicate.as_ref().map(Duplicate::results_by_hands),
+			});
+		}
+		self.send_all(ServerMessage::MatchEnd {
+			winning_team,
+			points: self.points,
+		});
+		true
+	}
+}
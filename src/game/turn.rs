@@ -0,0 +1,105 @@
+use crate::prelude::*;
+
+/// The action the game is currently waiting for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum Turn {
+	Bid {
+		player_id: usize,
+	},
+	/// Either player of the coinched team may surcoincher or pass
+	SurCoinche {
+		team: bool,
+	},
+	PlayCard {
+		player_id: usize,
+	},
+}
+
+#[derive(Debug, Serialize, Clone, Copy, JsonSchema)]
+pub struct TurnDeadline {
+	pub turn: Turn,
+	/// Unix timestamp (ms) after which the server acts on behalf of the player
+	pub deadline: i64,
+	/// Identifies the pending action, as the same `Turn` may come up several times in a row
+	#[serde(skip)]
+	pub step: (usize, usize),
+}
+
+impl Game {
+	pub fn current_turn(&self) -> Option<Turn> {
+		if self.pause.is_some() {
+			return None;
+		}
+		match &self.game_state {
+			GameState::Lobby => None,
+			GameState::Bidding { bids, coinche_state } => match coinche_state {
+				BiddingCoincheState::No => Some(Turn::Bid {
+					player_id: (bids.last().map_or(self.dealer_id, |b| b.player_id) + 1) % 4,
+				}),
+				BiddingCoincheState::Coinche { .. } => Some(Turn::SurCoinche {
+					team: Player::team(bids.last()?.player_id),
+				}),
+				BiddingCoincheState::Surcoinche { .. } => None,
+			},
+			GameState::Running(RunningGame { claim: Some(_), .. })
+			| GameState::Running(RunningGame { undo: Some(_), .. }) => None,
+			GameState::Running(running) => Some(Turn::PlayCard {
+				player_id: (running.board.starting_player_id + running.board.cards.len()) % 4,
+			}),
+		}
+	}
+
+	/// Players who have yet to act for `turn`
+	pub fn turn_player_ids(&self, turn: Turn) -> Vec<usize> {
+		match turn {
+			Turn::Bid { player_id } | Turn::PlayCard { player_id } => vec![player_id],
+			Turn::SurCoinche { team } => {
+				let player_skipped = match self.game_state {
+					GameState::Bidding {
+						coinche_state: BiddingCoincheState::Coinche { player_skipped, .. },
+						..
+					} => player_skipped,
+					_ => None,
+				};
+				self.player_ids()
+					.filter(|&id| Player::team(id) == team && Some(id) != player_skipped)
+					.collect()
+			}
+		}
+	}
+
+	/// Deal number and number of actions taken in that deal
+	pub fn turn_step(&self) -> (usize, usize) {
+		let progress = match &self.game_state {
+			GameState::Lobby => 0,
+			GameState::Bidding { bids, .. } => bids.len(),
+			GameState::Running(running) => running.tricks.len() * 4 + running.board.cards.len(),
+		};
+		(self.deal_count, progress)
+	}
+
+	/// Passes or plays the lowest legal card on behalf of the players who let `turn` time out
+	pub fn play_timed_out_turn(&mut self, turn: Turn) -> crate::Result<()> {
+		match turn {
+			Turn::Bid { player_id } => {
+				self.players[player_id].timeouts += 1;
+				self.player_mut(player_id).bid(None)?;
+			}
+			Turn::SurCoinche { .. } => {
+				for player_id in self.turn_player_ids(turn) {
+					self.players[player_id].timeouts += 1;
+					self.player_mut(player_id).surcoincher(false)?;
+				}
+			}
+			Turn::PlayCard { player_id } => {
+				let card = self
+					.player(player_id)
+					.lowest_playable_card()
+					.ok_or_else(|| err_msg("No playable card"))?;
+				self.players[player_id].timeouts += 1;
+				self.player_mut(player_id).play_card(PlayerCardIdentifier::Card(card))?;
+			}
+		}
+		Ok(())
+	}
+}
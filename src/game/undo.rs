@@ -0,0 +1,138 @@
+use crate::prelude::*;
+
+/// Last card played in the current trick, which may be taken back
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct LastPlay {
+	pub player_id: usize,
+	pub card_pos: usize,
+	pub card: Card,
+	pub belote_rebelote: Option<BeloteRebelote>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UndoRequest {
+	pub player_id: usize,
+	/// Players who accepted the take-back so far
+	pub accepted_by: Vec<usize>,
+	/// Unix timestamp (ms) after which the request is turned down
+	pub expires_at: i64,
+	#[serde(skip)]
+	pub expiry_scheduled: bool,
+}
+
+impl Game {
+	/// Asks the table to take back the card the player just played
+	pub fn request_undo(&mut self, player_id: usize) -> GameResult<()> {
+		let undo_window_secs = self.settings.undo_window_secs;
+		let running = match &mut self.game_state {
+			GameState::Running(running) => running,
+			_ => {
+				return Err(GameError::WrongPhase {
+					expected: Phase::Running,
+				})
+			}
+		};
+		if running.undo.is_some() {
+			return Err(GameError::UndoPending);
+		}
+		if running.claim.is_some() {
+			return Err(GameError::ClaimPending);
+		}
+		match running.last_play {
+			Some(last_play) if last_play.player_id == player_id => (),
+			_ => return Err(GameError::NothingToUndo),
+		}
+		running.undo = Some(UndoRequest {
+			player_id,
+			accepted_by: Vec::new(),
+			expires_at: (chrono::Utc::now() + chrono::Duration::seconds(undo_window_secs as i64)).timestamp_millis(),
+			expiry_scheduled: false,
+		});
+		self.send_all(ServerMessage::UndoRequest { player_id });
+		self.check_undo_accepted();
+		Ok(())
+	}
+
+	pub fn answer_undo(&mut self, player_id: usize, accept: bool) -> GameResult<()> {
+		let undo = match &mut self.game_state {
+			GameState::Running(RunningGame { undo: Some(undo), .. }) => undo,
+			_ => return Err(GameError::NoPendingUndo),
+		};
+		if undo.player_id == player_id {
+			return Err(GameError::OwnRequest);
+		}
+		if !accept {
+			self.resolve_undo(false);
+			return Ok(());
+		}
+		if !undo.accepted_by.contains(&player_id) {
+			undo.accepted_by.push(player_id);
+		}
+		self.check_undo_accepted();
+		Ok(())
+	}
+
+	/// Turns the pending request down if it is still the one expiring at `expires_at`
+	///
+	/// Nothing expires while paused: resuming gives the table a new window.
+	pub fn expire_undo(&mut self, expires_at: i64) {
+		if self.pause.is_some() {
+			return;
+		}
+		if let GameState::Running(RunningGame { undo: Some(undo), .. }) = &self.game_state {
+			if undo.expires_at == expires_at {
+				self.resolve_undo(false);
+			}
+		}
+	}
+
+	fn check_undo_accepted(&mut self) {
+		let undo = match &self.game_state {
+			GameState::Running(RunningGame { undo: Some(undo), .. }) => undo,
+			_ => return,
+		};
+		// Absent players can't object
+		let all_accepted = self
+			.player_ids()
+			.filter(|&id| id != undo.player_id && self.players[id].control == Control::Human)
+			.all(|id| undo.accepted_by.contains(&id));
+		if all_accepted {
+			self.resolve_undo(true);
+		}
+	}
+
+	/// Puts the card back into the player's hand if accepted
+	fn resolve_undo(&mut self, accepted: bool) {
+		let running = match &mut self.game_state {
+			GameState::Running(running) => running,
+			_ => return,
+		};
+		let undo = match running.undo.take() {
+			Some(undo) => undo,
+			None => return,
+		};
+		let last_play = match (accepted, running.last_play.take()) {
+			(true, Some(last_play)) => last_play,
+			(_, last_play) => {
+				running.last_play = last_play;
+				self.send_all(ServerMessage::UndoResult {
+					player_id: undo.player_id,
+					accepted: false,
+				});
+				return;
+			}
+		};
+		running.board.cards.pop();
+		if last_play.belote_rebelote == Some(BeloteRebelote::Belote) {
+			running.belote_player = None;
+		}
+		self.players[last_play.player_id]
+			.cards
+			.insert(last_play.card_pos, last_play.card);
+		self.send_all(ServerMessage::UndoResult {
+			player_id: undo.player_id,
+			accepted: true,
+		});
+		self.send_refresh_all_all();
+	}
+}
@@ -0,0 +1,290 @@
+use crate::prelude::*;
+
+/// Everything a seat may see of the table, so that clients can render from this alone
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TableView<'a> {
+	/// Seat of the viewer
+	pub player_id: usize,
+	pub players: Vec<SeatView<'a>>,
+	pub host_id: Option<usize>,
+	pub locked: bool,
+	pub settings: &'a Settings,
+	pub dealer_id: usize,
+	pub deal_count: usize,
+	pub points: [usize; 2],
+	pub round_points: &'a [RoundPoints],
+	pub phase: PhaseView<'a>,
+	/// The viewer's cards
+	pub hand: &'a [Card],
+	pub turn: Option<Turn>,
+	pub turn_deadline: Option<TurnDeadline>,
+	/// What the viewer may do right now
+	pub legal_actions: Vec<Action>,
+	pub pause: Option<&'a Pause>,
+	pub redeal_votes: &'a [usize],
+	pub duplicate: Option<&'a Duplicate>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SeatView<'a> {
+	pub username: &'a str,
+	pub account: Option<&'a str>,
+	pub control: Control,
+	pub timeouts: usize,
+	pub kick_votes: &'a [usize],
+	pub silenced: bool,
+	pub card_count: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub enum PhaseView<'a> {
+	Lobby,
+	Bidding {
+		bids: &'a [PlayerBid],
+		/// Player who coinched, if any
+		coincher_id: Option<usize>,
+	},
+	Playing {
+		bidder_id: usize,
+		bid: Bid,
+		coinche_state: CoincheState,
+		/// Cards of the trick being played
+		board: &'a Board,
+		last_trick: Option<&'a Trick>,
+		/// Number of tricks taken by each team
+		tricks: [usize; 2],
+		belote_player: Option<usize>,
+		claim: Option<&'a Claim>,
+		undo: Option<&'a UndoRequest>,
+	},
+}
+
+/// Client message the server would accept from the viewer
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum Action {
+	/// Any contract above `above`
+	Bid {
+		above: Option<BidScore>,
+	},
+	Pass,
+	Coinche,
+	/// Either surcoincher or pass
+	SurCoinche,
+	PlayCard {
+		cards: Vec<Card>,
+	},
+	Claim,
+	AnswerClaim,
+	RequestUndo,
+	AnswerUndo,
+	RequestRedeal,
+	Resume,
+}
+
+impl Game {
+	pub fn view(&self, player_id: usize) -> TableView<'_> {
+		let phase = match &self.game_state {
+			GameState::Lobby => PhaseView::Lobby,
+			GameState::Bidding { bids, coinche_state } => PhaseView::Bidding {
+				bids,
+				coincher_id: match *coinche_state {
+					BiddingCoincheState::No => None,
+					BiddingCoincheState::Coinche { player_id, .. } => Some(player_id),
+					BiddingCoincheState::Surcoinche { coincher_id, .. } => Some(coincher_id),
+				},
+			},
+			GameState::Running(running) => {
+				let mut tricks = [0, 0];
+				for trick in running.tricks.iter() {
+					tricks[Player::team(trick.winner_id) as usize] += 1;
+				}
+				PhaseView::Playing {
+					bidder_id: running.bidder_id,
+					bid: running.bid,
+					coinche_state: running.coinche_state,
+					board: &running.board,
+					last_trick: running.tricks.last(),
+					tricks,
+					belote_player: running.belote_player,
+					claim: running.claim.as_ref(),
+					undo: running.undo.as_ref(),
+				}
+			}
+		};
+		TableView {
+			player_id,
+			players: self
+				.players
+				.iter()
+				.map(|p| SeatView {
+					username: &p.username,
+					account: p.account.as_deref(),
+					control: p.control,
+					timeouts: p.timeouts,
+					kick_votes: &p.kick_votes,
+					silenced: p.silenced,
+					card_count: p.cards.len(),
+				})
+				.collect(),
+			host_id: self.host_id,
+			locked: self.password.is_some(),
+			settings: &self.settings,
+			dealer_id: self.dealer_id,
+			deal_count: self.deal_count,
+			points: self.points,
+			round_points: &self.round_points,
+			phase,
+			hand: &self.players[player_id].cards,
+			turn: self.current_turn(),
+			turn_deadline: self.turn_deadline,
+			legal_actions: self.legal_actions(player_id),
+			pause: self.pause.as_ref(),
+			redeal_votes: &self.redeal_votes,
+			duplicate: self.duplicate.as_ref(),
+		}
+	}
+
+	pub fn legal_actions(&self, player_id: usize) -> Vec<Action> {
+		let mut actions = Vec::new();
+		if let Some(pause) = &self.pause {
+			if !pause.resume_votes.contains(&player_id) {
+				actions.push(Action::Resume);
+			}
+			return actions;
+		}
+		let turn_is_ours = self
+			.current_turn()
+			.is_some_and(|turn| self.turn_player_ids(turn).contains(&player_id));
+		let team = Player::team(player_id);
+		match &self.game_state {
+			GameState::Lobby => (),
+			GameState::Bidding { bids, coinche_state } => {
+				let last_bid = bids.iter().rev().find_map(|b| Some((b.player_id, b.bid?)));
+				match coinche_state {
+					BiddingCoincheState::No => {
+						if turn_is_ours {
+							let above = last_bid.map(|(_, bid)| bid.score);
+							if above != Some(BidScore::Capot) {
+								actions.push(Action::Bid { above });
+							}
+							actions.push(Action::Pass);
+						}
+						if bids.last().is_some_and(|b| Player::team(b.player_id) != team) {
+							actions.push(Action::Coinche);
+						}
+					}
+					BiddingCoincheState::Coinche { .. } if turn_is_ours => actions.push(Action::SurCoinche),
+					_ => (),
+				}
+				if self.duplicate.is_none() && !self.redeal_votes.contains(&player_id) {
+					actions.push(Action::RequestRedeal);
+				}
+			}
+			GameState::Running(running) => match (&running.claim, &running.undo) {
+				(Some(claim), _) => {
+					if Player::team(claim.player_id) != team && !claim.accepted_by.contains(&player_id) {
+						actions.push(Action::AnswerClaim);
+					}
+				}
+				(None, Some(undo)) => {
+					if undo.player_id != player_id && !undo.accepted_by.contains(&player_id) {
+						actions.push(Action::AnswerUndo);
+					}
+				}
+				(None, None) => {
+					if turn_is_ours {
+						actions.push(Action::PlayCard {
+							cards: self.player(player_id).playable_cards().collect(),
+						});
+					}
+					if running.last_play.is_some_and(|p| p.player_id == player_id) {
+						actions.push(Action::RequestUndo);
+					}
+					actions.push(Action::Claim);
+				}
+			},
+		}
+		actions
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {super::*, crate::accounts::Accounts, actix::prelude::*};
+
+	/// Table of 4 guests playing over HTTP, dealt the first hand
+	fn dealt_game() -> Game {
+		let accounts_path = std::env::temp_dir().join(format!("coinche-view-test-{}.json", std::process::id()));
+		let accounts = Accounts::load(accounts_path).unwrap().start();
+		let mut game = Game::new(Settings::default(), accounts);
+		for username in ["a", "b", "c", "d"] {
+			game.players
+				.push(Player::new(username.to_owned(), None, None, Encoding::default()));
+		}
+		assert!(game.legal_actions(0).is_empty());
+		assert!(game.try_bidding_phase());
+		game
+	}
+
+	#[actix_web::test]
+	async fn bidding_actions() {
+		let mut game = dealt_game();
+		// Player 0 speaks first, after the dealer
+		assert_eq!(
+			game.legal_actions(0),
+			[Action::Bid { above: None }, Action::Pass, Action::RequestRedeal]
+		);
+		assert_eq!(game.legal_actions(1), [Action::RequestRedeal]);
+
+		let bid = Bid {
+			trump: Trump::Suit(Suit::Hearts),
+			score: BidScore::_80,
+		};
+		game.player_mut(0).bid(Some(bid)).unwrap();
+		assert_eq!(
+			game.legal_actions(1),
+			[
+				Action::Bid {
+					above: Some(BidScore::_80)
+				},
+				Action::Pass,
+				Action::Coinche,
+				Action::RequestRedeal
+			]
+		);
+		// Opponents may coinche out of turn, partners may not
+		assert_eq!(game.legal_actions(2), [Action::RequestRedeal]);
+		assert_eq!(game.legal_actions(3), [Action::Coinche, Action::RequestRedeal]);
+
+		game.redeal_votes.push(3);
+		assert_eq!(game.legal_actions(3), [Action::Coinche]);
+	}
+
+	#[actix_web::test]
+	async fn playing_actions() {
+		let mut game = dealt_game();
+		let bid = Bid {
+			trump: Trump::Suit(Suit::Hearts),
+			score: BidScore::_80,
+		};
+		game.player_mut(0).bid(Some(bid)).unwrap();
+		for player_id in 1..4 {
+			game.player_mut(player_id).bid(None).unwrap();
+		}
+		let cards = game.players[0].cards.clone();
+		assert_eq!(game.legal_actions(0), [Action::PlayCard { cards }, Action::Claim]);
+		assert_eq!(game.legal_actions(1), [Action::Claim]);
+	}
+
+	#[actix_web::test]
+	async fn paused_tables_only_resume() {
+		let mut game = dealt_game();
+		game.pause = Some(Pause {
+			player_id: 0,
+			resume_votes: vec![1],
+			turn_time_left: None,
+		});
+		assert_eq!(game.legal_actions(0), [Action::Resume]);
+		assert!(game.legal_actions(1).is_empty());
+	}
+}
@@ -1,8 +1,12 @@
+pub mod accounts;
 pub mod game;
 pub mod logging;
 pub mod messages;
+pub mod notifier;
 pub mod player;
+pub mod protocol;
 pub mod server;
+pub mod tournament;
 
 #[macro_use]
 extern crate serde_derive;
@@ -14,21 +18,25 @@ pub type Result<T> = std::result::Result<T, failure::Error>;
 mod prelude {
 	use super::*;
 	pub use {
+		accounts::Identity,
 		cards::*,
+		chat::ChatMessage,
+		claim::Claim,
 		contract::*,
+		duplicate::Duplicate,
+		error::{GameError, GameResult, IllegalPlay, Phase},
 		game::*,
-		messages::{ClientMessage, ServerMessage},
+		messages::{ClientMessage, EncodedMessage, Encoding, SeatChange, ServerMessage},
+		pause::Pause,
 		player::*,
+		reaction::Reaction,
+		redeal::Misdeal,
 		server::websocket::WebSocket,
+		settings::*,
+		turn::*,
+		undo::*,
+		view::TableView,
 	};
 
-	pub use {
-		actix::Addr,
-		failure::err_msg,
-		futures::prelude::*,
-		std::{
-			borrow::Borrow,
-			sync::{Arc, Mutex, MutexGuard},
-		},
-	};
+	pub use {actix::Addr, failure::err_msg, futures::prelude::*, schemars::JsonSchema, std::borrow::Borrow};
 }
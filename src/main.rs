@@ -1,6 +1,6 @@
 use coinche::*;
 
-use std::env;
+use std::{env, path::PathBuf, sync::Arc};
 
 #[actix_web::main]
 async fn main() {
@@ -8,5 +8,20 @@ async fn main() {
 	let port: u16 = env::var("PORT")
 		.ok()
 		.map_or(3000, |p| p.parse().expect("Invalid port value in env var"));
-	server::start(port).await;
+	let accounts_file = env::var("ACCOUNTS_FILE").map_or_else(|_| PathBuf::from("accounts.json"), PathBuf::from);
+	let accounts = accounts::Accounts::load(accounts_file).expect("Failed to load accounts");
+	// Told whenever a seat has to act
+	let notifier = env::var("TURN_WEBHOOK_URL")
+		.ok()
+		.map(|url| Arc::new(notifier::Webhook::new(url)) as Arc<dyn notifier::TurnNotifier>);
+	// Set when deployed behind a proxy (e.g. on Heroku), whose `X-Forwarded-For` can then be trusted
+	let behind_proxy = server::accounts::BehindProxy(env::var("BEHIND_PROXY").is_ok_and(|v| v == "1" || v == "true"));
+	server::start(
+		port,
+		game::settings::Settings::from_env(),
+		accounts,
+		notifier,
+		behind_proxy,
+	)
+	.await;
 }
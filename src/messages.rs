@@ -1,32 +1,147 @@
 use crate::prelude::*;
 
-#[derive(Debug, Deserialize)]
+/// Bumped on any change to the messages that older clients can't cope with
+pub const PROTOCOL_VERSION: u32 = 2;
+/// Optional parts of the protocol this server supports, announced in `ServerMessage::Welcome`
+pub const PROTOCOL_FEATURES: &[&str] = &["error_codes", "request_ids", "event_seq", "msgpack", "seat_tokens"];
+
+/// How messages go through a websocket, picked when connecting with `?encoding=`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+	/// Text frames
+	#[default]
+	Json,
+	/// Binary frames, with cards sent as a single byte
+	MsgPack,
+}
+
+#[derive(Debug, Clone)]
+pub enum EncodedMessage {
+	Text(String),
+	Binary(Vec<u8>),
+}
+
+impl Encoding {
+	pub fn encode(self, message: &impl serde::Serialize) -> EncodedMessage {
+		match self {
+			Self::Json => EncodedMessage::Text(serde_json::to_string(message).unwrap()),
+			Self::MsgPack => EncodedMessage::Binary(rmp_serde::to_vec_named(message).unwrap()),
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum ClientMessage {
-	Init { username: String },
+	Init {
+		username: String,
+		/// Required to join a locked table
+		#[serde(default)]
+		password: Option<String>,
+		/// Clients predating versioning speak the first version
+		#[serde(default = "default_protocol_version")]
+		protocol_version: u32,
+		/// `seq` of the last message received before losing the connection, to only get the ones missed
+		#[serde(default)]
+		last_seq: Option<u64>,
+		/// Given in `Welcome`, lets guests take their seat back after losing the connection
+		#[serde(default)]
+		seat_token: Option<String>,
+	},
 	RefreshGameState,
+	/// Ask again for the messages that followed `last_seq`, e.g. after noticing a gap in `seq`s.
+	/// The whole table is sent instead if some of them aren't kept anymore.
+	Replay {
+		last_seq: u64,
+	},
 	Bid(Option<Bid>),
 	Coinche,
 	SurCoinche(bool),
 	PlayCard(PlayerCardIdentifier),
-}
-
-#[derive(Debug, Serialize)]
-pub enum ServerMessage<'a> {
-	/// Player list, ids and points
-	Game {
-		game: &'a Game,
+	Chat(String),
+	React(Reaction),
+	/// Hide (or show again) a player's chat messages and reactions
+	Mute {
 		player_id: usize,
+		muted: bool,
 	},
-	/// For the connected player
-	Cards {
+	/// Take all the remaining tricks
+	Claim,
+	AnswerClaim(bool),
+	/// Take back the card just played
+	RequestUndo,
+	AnswerUndo(bool),
+	/// Ask for the cards to be dealt again during bidding
+	RequestRedeal,
+	Pause,
+	/// Agree to resume play
+	Resume,
+	/// Free the seat for someone else
+	Leave,
+	VoteKick {
 		player_id: usize,
-		cards: &'a [Card],
 	},
-	/// For the other players
-	CardCount {
+	// Host only
+	SetSettings(Settings),
+	SetPassword(Option<String>),
+	/// New seat order, as a list of current player ids
+	ReorderSeats(Vec<usize>),
+	Restart,
+	/// Prevent a player from chatting
+	Silence {
+		player_id: usize,
+		silenced: bool,
+	},
+	TransferHost {
 		player_id: usize,
-		count: usize,
 	},
+}
+
+/// A `ClientMessage`, which may be sent as `{"request_id": 1, "message": ...}`
+/// to get an `Ack` or an `Error` carrying the same id in response
+#[derive(Debug, JsonSchema)]
+pub struct ClientRequest {
+	pub request_id: Option<u64>,
+	pub message: ClientMessage,
+}
+
+impl ClientRequest {
+	/// The request id is kept even if the message turns out to be invalid
+	pub fn from_json(text: &str) -> Result<Self, (Option<u64>, String)> {
+		Self::from_value(serde_json::from_str(text).map_err(|e| (None, e.to_string()))?)
+	}
+
+	/// Same structure as the JSON, MessagePack encoded
+	pub fn from_msgpack(bytes: &[u8]) -> Result<Self, (Option<u64>, String)> {
+		Self::from_value(rmp_serde::from_slice(bytes).map_err(|e| (None, e.to_string()))?)
+	}
+
+	fn from_value(mut value: serde_json::Value) -> Result<Self, (Option<u64>, String)> {
+		let (request_id, message) = match value.get("request_id").and_then(serde_json::Value::as_u64) {
+			Some(request_id) => (Some(request_id), value["message"].take()),
+			None => (None, value),
+		};
+		match serde_json::from_value(message) {
+			Ok(message) => Ok(Self { request_id, message }),
+			Err(e) => Err((request_id, e.to_string())),
+		}
+	}
+}
+
+fn default_protocol_version() -> u32 {
+	1
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub enum ServerMessage<'a> {
+	/// Answer to `Init` once a seat is granted, before anything else
+	Welcome {
+		protocol_version: u32,
+		features: &'static [&'static str],
+		seat_token: &'a str,
+	},
+	/// Whole state of the table as seen from the connected player's seat
+	Table(Box<TableView<'a>>),
 	PlayerBid(PlayerBid),
 	Coinche {
 		player_id: usize,
@@ -43,31 +158,164 @@ pub enum ServerMessage<'a> {
 	Trick {
 		winner_id: usize,
 	},
+	/// A player disconnected, reconnected or got replaced by a bot
+	Control {
+		player_id: usize,
+		control: Control,
+	},
+	/// Players who voted for kicking `player_id` so far
+	KickVotes {
+		player_id: usize,
+		votes: &'a [usize],
+	},
+	Chat(&'a ChatMessage),
+	/// Recent messages, minus those of muted players
+	ChatHistory {
+		messages: Vec<&'a ChatMessage>,
+	},
+	Silenced {
+		player_id: usize,
+		silenced: bool,
+	},
+	Reaction {
+		player_id: usize,
+		reaction: Reaction,
+	},
+	MatchEnd {
+		/// `None` on a tie
+		winning_team: Option<bool>,
+		points: [usize; 2],
+	},
+	/// The claimer reveals their hand
+	Claim {
+		player_id: usize,
+		cards: &'a [Card],
+		/// The claim couldn't be checked by the server: opponents have to accept it
+		pending: bool,
+	},
+	ClaimResult {
+		player_id: usize,
+		accepted: bool,
+	},
+	/// The player asks to take back their last card
+	UndoRequest {
+		player_id: usize,
+	},
+	UndoResult {
+		player_id: usize,
+		accepted: bool,
+	},
+	/// Players who asked for the cards to be dealt again so far
+	RedealVotes {
+		votes: &'a [usize],
+	},
+	/// Cards were dealt again because of that player's hand
+	Misdeal {
+		player_id: usize,
+		misdeal: Misdeal,
+		/// Still a misdeal after too many deals: the hand is played anyway
+		waived: bool,
+	},
+	Host {
+		player_id: usize,
+	},
+	/// The game got paused, a player agreed to resume, or the game resumed
+	Pause {
+		pause: Option<&'a Pause>,
+	},
+	/// The server will act on behalf of the player if the turn isn't played by then
+	TurnDeadline(TurnDeadline),
+	/// The request with that id was handled successfully
+	Ack {
+		request_id: u64,
+	},
 	Error {
-		message: &'a str,
+		#[serde(flatten)]
+		error: &'a GameError,
+		/// Human readable, for display
+		message: String,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		request_id: Option<u64>,
 	},
 }
 
+/// How handling a message affected the seat of the sending client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeatChange {
+	Unchanged,
+	Seated(usize),
+	Left,
+}
+
 impl Game {
 	pub fn handle_msg(
 		&mut self,
 		player_id: Option<usize>,
 		msg: ClientMessage,
-		web_socket: Addr<WebSocket>,
-	) -> crate::Result<Option<usize>> {
+		identity: Option<Identity>,
+		web_socket: Option<Addr<WebSocket>>,
+		encoding: Encoding,
+	) -> GameResult<SeatChange> {
 		match player_id {
 			None => match msg {
-				ClientMessage::Init { username } => {
-					return Ok(Some(self.add_player(Player::new(username, web_socket))?));
+				ClientMessage::Init {
+					username,
+					password,
+					protocol_version,
+					last_seq,
+					seat_token,
+				} => {
+					if protocol_version != PROTOCOL_VERSION {
+						return Err(GameError::UnsupportedProtocol {
+							protocol_version: PROTOCOL_VERSION,
+						});
+					}
+					// Logged in players play under their account's name
+					let player = match identity {
+						Some(identity) => {
+							Player::new(identity.display_name, Some(identity.username), web_socket, encoding)
+						}
+						None => Player::new(username, None, web_socket, encoding),
+					};
+					return Ok(SeatChange::Seated(
+						self.add_player(player, password, seat_token, last_seq)?,
+					));
 				}
-				_ => return Err(err_msg("Client not initialized")),
+				_ => return Err(GameError::NotInitialized),
 			},
 			Some(player_id) => {
+				match msg {
+					ClientMessage::Bid(_)
+					| ClientMessage::Coinche
+					| ClientMessage::SurCoinche(_)
+					| ClientMessage::PlayCard(_)
+					| ClientMessage::RequestRedeal
+					| ClientMessage::Claim
+					| ClientMessage::AnswerClaim(_)
+					| ClientMessage::RequestUndo
+					| ClientMessage::AnswerUndo(_)
+						if self.pause.is_some() =>
+					{
+						return Err(GameError::Paused)
+					}
+					ClientMessage::SetSettings(_)
+					| ClientMessage::SetPassword(_)
+					| ClientMessage::ReorderSeats(_)
+					| ClientMessage::Restart
+					| ClientMessage::Silence { .. }
+					| ClientMessage::TransferHost { .. } => self.check_host(player_id)?,
+					_ => {}
+				}
 				let mut player = self.player_mut(player_id);
 				match msg {
-					ClientMessage::Init { .. } => return Err(err_msg("Already initialized")),
+					ClientMessage::Init { .. } => return Err(GameError::AlreadyInitialized),
 					ClientMessage::RefreshGameState => {
-						player.send_refresh_all()?;
+						let _ = player.send_refresh_all();
+					}
+					ClientMessage::Replay { last_seq } => {
+						if !player.replay(last_seq) {
+							let _ = player.send_refresh_all();
+						}
 					}
 					ClientMessage::Bid(bid) => {
 						player.bid(bid)?;
@@ -81,15 +329,86 @@ impl Game {
 					ClientMessage::PlayCard(card_identifier) => {
 						player.play_card(card_identifier)?;
 					}
+					ClientMessage::Chat(text) => {
+						self.chat(player_id, text)?;
+					}
+					ClientMessage::React(reaction) => {
+						self.react(player_id, reaction)?;
+					}
+					ClientMessage::Mute {
+						player_id: muted_player_id,
+						muted,
+					} => {
+						self.mute(player_id, muted_player_id, muted)?;
+					}
+					ClientMessage::Claim => {
+						self.claim(player_id)?;
+					}
+					ClientMessage::AnswerClaim(accept) => {
+						self.answer_claim(player_id, accept)?;
+					}
+					ClientMessage::RequestUndo => {
+						self.request_undo(player_id)?;
+					}
+					ClientMessage::AnswerUndo(accept) => {
+						self.answer_undo(player_id, accept)?;
+					}
+					ClientMessage::RequestRedeal => {
+						self.request_redeal(player_id)?;
+					}
+					ClientMessage::Leave => {
+						self.vacate_seat(player_id);
+						return Ok(SeatChange::Left);
+					}
+					ClientMessage::VoteKick {
+						player_id: kicked_player_id,
+					} => {
+						self.vote_kick(player_id, kicked_player_id)?;
+					}
+					ClientMessage::SetSettings(settings) => {
+						self.set_settings(settings)?;
+					}
+					ClientMessage::SetPassword(password) => {
+						self.set_password(password);
+					}
+					ClientMessage::ReorderSeats(order) => {
+						self.reorder_seats(&order)?;
+						// The sender may have been moved too
+						return Ok(SeatChange::Seated(
+							order.iter().position(|&id| id == player_id).unwrap(),
+						));
+					}
+					ClientMessage::Pause => {
+						self.pause(player_id)?;
+					}
+					ClientMessage::Resume => {
+						self.vote_resume(player_id)?;
+					}
+					ClientMessage::Silence {
+						player_id: silenced_player_id,
+						silenced,
+					} => {
+						self.silence(silenced_player_id, silenced)?;
+					}
+					ClientMessage::Restart => {
+						self.restart();
+					}
+					ClientMessage::TransferHost { player_id: new_host_id } => {
+						self.transfer_host(new_host_id)?;
+					}
 				}
 			}
 		}
-		Ok(None)
+		Ok(SeatChange::Unchanged)
 	}
 }
 
 impl<'a> ServerMessage<'a> {
-	pub fn to_json_string(&self) -> String {
-		serde_json::to_string(self).unwrap()
+	pub fn error(error: &'a GameError, request_id: Option<u64>) -> Self {
+		Self::Error {
+			error,
+			message: error.to_string(),
+			request_id,
+		}
 	}
 }
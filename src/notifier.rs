@@ -0,0 +1,173 @@
+use crate::prelude::*;
+
+use {crate::game::view::Action, std::fmt};
+
+/// Told by the game when seats have to act, e.g. to let players of slow games know it's their turn
+pub trait TurnNotifier: fmt::Debug + Send + Sync {
+	fn notify(&self, notification: TurnNotification);
+}
+
+#[derive(Debug, Serialize)]
+pub struct TurnNotification {
+	/// `None` for the main table
+	pub table_id: Option<String>,
+	pub player_id: usize,
+	pub username: String,
+	pub account: Option<String>,
+	pub turn: Turn,
+	/// What the player may do
+	pub actions: Vec<NotifiedAction>,
+	/// Unix timestamp (ms) after which the server acts on behalf of the player
+	pub deadline: Option<i64>,
+}
+
+/// `Action` without the cards that may be played: the notifier isn't trusted with hands
+#[derive(Debug, Serialize)]
+pub enum NotifiedAction {
+	Bid { above: Option<BidScore> },
+	Pass,
+	Coinche,
+	SurCoinche,
+	PlayCard,
+	Claim,
+	AnswerClaim,
+	RequestUndo,
+	AnswerUndo,
+	RequestRedeal,
+	Resume,
+}
+
+impl From<Action> for NotifiedAction {
+	fn from(action: Action) -> Self {
+		match action {
+			Action::Bid { above } => Self::Bid { above },
+			Action::Pass => Self::Pass,
+			Action::Coinche => Self::Coinche,
+			Action::SurCoinche => Self::SurCoinche,
+			Action::PlayCard { .. } => Self::PlayCard,
+			Action::Claim => Self::Claim,
+			Action::AnswerClaim => Self::AnswerClaim,
+			Action::RequestUndo => Self::RequestUndo,
+			Action::AnswerUndo => Self::AnswerUndo,
+			Action::RequestRedeal => Self::RequestRedeal,
+			Action::Resume => Self::Resume,
+		}
+	}
+}
+
+/// POSTs notifications as JSON to `url`
+#[derive(Debug)]
+pub struct Webhook {
+	url: String,
+	client: reqwest::Client,
+}
+
+impl Webhook {
+	pub fn new(url: String) -> Self {
+		Self {
+			url,
+			client: reqwest::Client::new(),
+		}
+	}
+}
+
+impl TurnNotifier for Webhook {
+	fn notify(&self, notification: TurnNotification) {
+		let request = self.client.post(&self.url).json(&notification).send();
+		actix::spawn(async move {
+			match request.await.and_then(|response| response.error_for_status()) {
+				Ok(_) => (),
+				Err(err) => warn!("Turn webhook failed for seat {}: {}", notification.player_id, err),
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use {
+		crate::{
+			accounts::Accounts,
+			server::game::{HttpAction, HttpJoin},
+		},
+		actix::Actor,
+		actix_web::{web, App, HttpResponse, HttpServer},
+		std::{sync::Arc, time::Duration},
+		tokio::{sync::mpsc, time::timeout},
+	};
+
+	/// Next of the notifications POSTed to the stub server
+	async fn next_notification(received: &mut mpsc::UnboundedReceiver<serde_json::Value>) -> serde_json::Value {
+		timeout(Duration::from_secs(5), received.recv())
+			.await
+			.expect("No notification received")
+			.unwrap()
+	}
+
+	#[actix_web::test]
+	async fn webhook_posts_turns_without_cards() {
+		let (sender, mut received) = mpsc::unbounded_channel();
+		let stub = HttpServer::new(move || {
+			let sender = sender.clone();
+			App::new().route(
+				"/",
+				web::post().to(move |notification: web::Json<serde_json::Value>| {
+					sender.send(notification.into_inner()).unwrap();
+					async { HttpResponse::Ok().finish() }
+				}),
+			)
+		})
+		.workers(1)
+		.bind(("127.0.0.1", 0))
+		.unwrap();
+		let url = format!("http://{}/", stub.addrs()[0]);
+		actix_web::rt::spawn(stub.run());
+
+		let accounts_path = std::env::temp_dir().join(format!("coinche-notifier-test-{}.json", std::process::id()));
+		let accounts = Accounts::load(accounts_path).unwrap().start();
+		let mut game = Game::new(Settings::default(), accounts);
+		game.notifier = Some(Arc::new(Webhook::new(url)));
+		game.table_id = Some("test".to_owned());
+		let game = game.start();
+		let mut tokens = Vec::new();
+		for username in ["a", "b", "c", "d"] {
+			let join = HttpJoin {
+				username: username.to_owned(),
+				password: None,
+				seat_token: None,
+				identity: None,
+			};
+			tokens.push(game.send(join).await.unwrap().unwrap().1);
+		}
+
+		// The player after the dealer bids first
+		let notification = next_notification(&mut received).await;
+		assert_eq!(notification["table_id"], "test");
+		assert_eq!(notification["player_id"], 0);
+		assert_eq!(notification["username"], "a");
+		assert_eq!(notification["turn"], serde_json::json!({ "Bid": { "player_id": 0 } }));
+
+		// Then everybody else passes, and the bidder plays first
+		let bid = Bid {
+			trump: Trump::Suit(Suit::Hearts),
+			score: BidScore::_80,
+		};
+		let mut notification = serde_json::Value::Null;
+		for (player_id, token) in tokens.iter().enumerate() {
+			let action = HttpAction {
+				token: token.clone(),
+				message: ClientMessage::Bid(Some(bid).filter(|_| player_id == 0)),
+			};
+			game.send(action).await.unwrap().unwrap();
+			notification = next_notification(&mut received).await;
+		}
+		assert_eq!(notification["player_id"], 0);
+		assert_eq!(
+			notification["turn"],
+			serde_json::json!({ "PlayCard": { "player_id": 0 } })
+		);
+		assert_eq!(notification["actions"], serde_json::json!(["PlayCard", "Claim"]));
+	}
+}
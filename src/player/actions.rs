@@ -1,9 +1,9 @@
 use crate::prelude::*;
 
-use std::{cmp::Ordering, ops::DerefMut};
+use std::ops::DerefMut;
 
 impl<G: DerefMut<Target = Game>> PlayerPtr<G> {
-	pub fn bid(&mut self, bid: Option<Bid>) -> crate::Result<()> {
+	pub fn bid(&mut self, bid: Option<Bid>) -> GameResult<()> {
 		let dealer_id = self.game.dealer_id;
 		match self.game.game_state {
 			GameState::Bidding {
@@ -19,7 +19,7 @@ impl<G: DerefMut<Target = Game>> PlayerPtr<G> {
 						};
 						match (bid.map(|b| b.score), bids.last().and_then(|b| b.bid).map(|b| b.score)) {
 							(Some(bid), Some(prev_bid)) if bid <= prev_bid => {
-								return Err(err_msg("New bid has to be greater than previous bid"))
+								return Err(GameError::BidTooLow { previous: prev_bid })
 							}
 							_ => {}
 						}
@@ -28,23 +28,23 @@ impl<G: DerefMut<Target = Game>> PlayerPtr<G> {
 						for player in self.game.players() {
 							let _ = player.send_player_bid(player_bid);
 						}
-						if can_start_game {
-							if !self.game.try_playing_phase() {
-								return Err(err_msg("Could not start game"));
-							}
+						if can_start_game && !self.game.try_playing_phase() {
+							return Err(GameError::Internal);
 						}
 						Ok(())
 					} else {
-						Err(err_msg("Not your turn"))
+						Err(GameError::NotYourTurn)
 					}
 				}
-				_ => Err(err_msg("Already coinch-ed")),
+				_ => Err(GameError::AlreadyCoinched),
 			},
-			_ => Err(err_msg("Not in bidding phase")),
+			_ => Err(GameError::WrongPhase {
+				expected: Phase::Bidding,
+			}),
 		}
 	}
 
-	pub fn coincher(&mut self) -> crate::Result<()> {
+	pub fn coincher(&mut self) -> GameResult<()> {
 		let team = self.team();
 		let game = self.game.deref_mut();
 		match game.game_state {
@@ -63,16 +63,18 @@ impl<G: DerefMut<Target = Game>> PlayerPtr<G> {
 						});
 						Ok(())
 					} else {
-						Err(err_msg("No opponent's bid to 'coincher'"))
+						Err(GameError::NoOpponentBid)
 					}
 				}
-				_ => Err(err_msg("Game is in non-coinchable state")),
+				_ => Err(GameError::AlreadyCoinched),
 			},
-			_ => Err(err_msg("Not in bidding phase")),
+			_ => Err(GameError::WrongPhase {
+				expected: Phase::Bidding,
+			}),
 		}
 	}
 
-	pub fn surcoincher(&mut self, do_surcoinche: bool) -> crate::Result<()> {
+	pub fn surcoincher(&mut self, do_surcoinche: bool) -> GameResult<()> {
 		let team = self.team();
 		let game = self.game.deref_mut();
 		match game.game_state {
@@ -104,156 +106,117 @@ impl<G: DerefMut<Target = Game>> PlayerPtr<G> {
 								}
 							};
 							if start_game && !self.game.try_playing_phase() {
-								Err(err_msg("Could not start game"))
+								Err(GameError::Internal)
 							} else {
 								Ok(())
 							}
 						}
-						_ => Err(err_msg("Game is in non-sur-coinchable state")),
+						_ => Err(GameError::NotCoinched),
 					}
 				} else {
-					Err(err_msg("Nothing to 'surcoincher'"))
+					Err(GameError::NotCoinchedTeam)
 				}
 			}
-			_ => Err(err_msg("Not in bidding phase")),
+			_ => Err(GameError::WrongPhase {
+				expected: Phase::Bidding,
+			}),
 		}
 	}
 
-	pub fn play_card(&mut self, card_identifier: PlayerCardIdentifier) -> crate::Result<bool> {
+	pub fn play_card(&mut self, card_identifier: PlayerCardIdentifier) -> GameResult<()> {
 		let team = self.team();
 		let game = self.game.deref_mut();
 		let players = &mut game.players;
 		let player = &players[self.player_id];
-		let cards = &player.cards;
 		match game.game_state {
-			GameState::Running(RunningGame {
-				ref mut board,
-				ref mut tricks,
-				ref bid,
-				ref mut belote_player,
-				..
-			}) => {
-				if ((board.starting_player_id + board.cards.len()) % 4) == self.player_id {
+			GameState::Running(RunningGame { claim: Some(_), .. }) => Err(GameError::ClaimPending),
+			GameState::Running(RunningGame { undo: Some(_), .. }) => Err(GameError::UndoPending),
+			GameState::Running(ref mut running) => {
+				if ((running.board.starting_player_id + running.board.cards.len()) % 4) == self.player_id {
 					if let Some((card_pos, try_play_card)) = player.find_card(card_identifier) {
 						// There's a chance we can play: it's our turn in the proper state.
 						// Let's now check if the play is valid
-						let can_play = if let Some(asked_suit) = board.cards.first().map(|c| c.suit) {
-							if cards.iter().any(|c| c.suit == asked_suit) {
-								// Forced to play the asked suit
-								if try_play_card.suit != asked_suit {
-									false
-								} else {
-									// We're the right suit. But right number?
-									if bid.trump.is_trump(asked_suit) {
-										// Forced to play higher if possible
-										let high_trump_value = board.high_trump_value(asked_suit).unwrap();
-										high_trump_value.cmp_trump(&try_play_card.value) == Ordering::Less
-											|| cards
-												.iter()
-												.filter(|c| c.suit == asked_suit)
-												.all(|c| c.value.cmp_trump(&high_trump_value) == Ordering::Less)
-									} else {
-										true
-									}
-								}
-							} else {
-								let should_play_trump: Option<Suit> = match bid.trump {
-									Trump::Suit(trump_suit) => {
-										if Player::team(board.winning_player_id(bid.trump).unwrap()) != team
-											&& cards.iter().any(|c| c.suit == trump_suit)
-										{
-											Some(trump_suit)
-										} else {
-											None
+						running
+							.check_play(&player.cards, try_play_card, team)
+							.map_err(|reason| GameError::IllegalCard { reason })?;
+						let RunningGame {
+							ref mut board,
+							ref mut tricks,
+							ref bid,
+							ref mut belote_player,
+							ref mut last_play,
+							..
+						} = *running;
+						board.cards.push(try_play_card);
+						players[self.player_id].cards.remove(card_pos);
+						let mut belote_notification = None;
+						if let Trump::Suit(trump_suit) = bid.trump {
+							if try_play_card.suit == trump_suit {
+								if belote_player.is_none() {
+									let look_for_value = match try_play_card.value {
+										Value::King => Some(Value::Queen),
+										Value::Queen => Some(Value::King),
+										_ => None,
+									};
+									if let Some(look_for_value) = look_for_value {
+										if players[self.player_id].cards.contains(&Card {
+											suit: trump_suit,
+											value: look_for_value,
+										}) {
+											*belote_player = Some(self.player_id);
+											belote_notification = Some(BeloteRebelote::Belote);
 										}
 									}
-									_ => None,
-								};
-								if let Some(trump_suit) = should_play_trump {
-									if try_play_card.suit != trump_suit {
-										false
-									} else {
-										// We're the right suit (trump). But right number ?
-										if let Some(high_trump_value) = board.high_trump_value(trump_suit) {
-											high_trump_value.cmp_trump(&try_play_card.value) == Ordering::Less
-												|| cards
-													.iter()
-													.filter(|c| c.suit == asked_suit)
-													.all(|c| c.value.cmp_trump(&high_trump_value) == Ordering::Less)
-										} else {
-											true
+								} else if *belote_player == Some(self.player_id) {
+									match try_play_card.value {
+										Value::King | Value::Queen => {
+											belote_notification = Some(BeloteRebelote::Rebelote)
 										}
-									}
-								} else {
-									true
+										_ => (),
+									};
 								}
 							}
-						} else {
-							true
-						};
-						if can_play {
-							board.cards.push(try_play_card);
-							players[self.player_id].cards.remove(card_pos);
-							let mut belote_notification = None;
-							if let Trump::Suit(trump_suit) = bid.trump {
-								if try_play_card.suit == trump_suit {
-									if belote_player.is_none() {
-										let look_for_value = match try_play_card.value {
-											Value::King => Some(Value::Queen),
-											Value::Queen => Some(Value::King),
-											_ => None,
-										};
-										if let Some(look_for_value) = look_for_value {
-											if players[self.player_id].cards.contains(&Card {
-												suit: trump_suit,
-												value: look_for_value,
-											}) {
-												*belote_player = Some(self.player_id);
-												belote_notification = Some(BeloteRebelote::Belote);
-											}
-										}
-									} else if *belote_player == Some(self.player_id) {
-										match try_play_card.value {
-											Value::King | Value::Queen => {
-												belote_notification = Some(BeloteRebelote::Rebelote)
-											}
-											_ => (),
-										};
-									}
-								}
-							}
-							for player in players.iter() {
-								let _ = player.send(ServerMessage::PlayedCard {
-									player_id: self.player_id,
-									card: try_play_card,
-									card_pos,
-									belote_rebelote: belote_notification,
-								});
-							}
-							// See if that closes the trick
-							if board.cards.len() == 4 {
-								let winner_id = board.winning_player_id(bid.trump).unwrap();
-								tricks.push(Trick {
-									starting_player_id: board.starting_player_id,
-									winner_id,
-									cards: std::mem::replace(&mut board.cards, Vec::new()),
-								});
-								board.starting_player_id = winner_id;
-								for player in game.players() {
-									let _ = player.send(ServerMessage::Trick { winner_id });
-								}
+						}
+						*last_play = Some(LastPlay {
+							player_id: self.player_id,
+							card_pos,
+							card: try_play_card,
+							belote_rebelote: belote_notification,
+						});
+						for player in players.iter() {
+							let _ = player.send(ServerMessage::PlayedCard {
+								player_id: self.player_id,
+								card: try_play_card,
+								card_pos,
+								belote_rebelote: belote_notification,
+							});
+						}
+						// See if that closes the trick
+						if board.cards.len() == 4 {
+							let winner_id = board.winning_player_id(bid.trump).unwrap();
+							tricks.push(Trick {
+								starting_player_id: board.starting_player_id,
+								winner_id,
+								cards: std::mem::take(&mut board.cards),
+							});
+							board.starting_player_id = winner_id;
+							*last_play = None;
+							for player in game.players() {
+								let _ = player.send(ServerMessage::Trick { winner_id });
 							}
-							game.try_end();
 						}
-						Ok(can_play)
+						game.try_end();
+						Ok(())
 					} else {
-						Err(err_msg("Invalid card pos"))
+						Err(GameError::InvalidCard)
 					}
 				} else {
-					Err(err_msg("Not your turn"))
+					Err(GameError::NotYourTurn)
 				}
 			}
-			_ => Err(err_msg("Games not in running state")),
+			_ => Err(GameError::WrongPhase {
+				expected: Phase::Running,
+			}),
 		}
 	}
 }
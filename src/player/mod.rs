@@ -1,41 +1,146 @@
 pub mod actions;
+pub mod outbox;
 
 use crate::prelude::*;
 
-use std::ops::{Deref, DerefMut};
+use {
+	crate::{
+		messages::{PROTOCOL_FEATURES, PROTOCOL_VERSION},
+		server::websocket::EncodedServerMessage,
+	},
+	actix::SpawnHandle,
+	outbox::Outbox,
+	rand::{distributions::Alphanumeric, Rng},
+	std::{
+		cell::RefCell,
+		ops::{Deref, DerefMut},
+		time::Instant,
+	},
+};
+
+const SEAT_TOKEN_LEN: usize = 32;
 
 #[derive(Serialize)]
 pub struct Player {
 	pub username: String,
+	/// Registered account playing the seat, if logged in
+	pub account: Option<String>,
+	/// Number of times the server had to act on behalf of the player
+	pub timeouts: usize,
+	pub control: Control,
+	/// Players who voted for kicking this one
+	pub kick_votes: Vec<usize>,
+	/// Not allowed to chat by the host
+	pub silenced: bool,
+	/// Players whose chat messages this one doesn't want to see
+	#[serde(skip)]
+	pub muted: Vec<usize>,
+	#[serde(skip)]
+	pub chat_times: Vec<Instant>,
+	#[serde(skip)]
+	pub last_reaction: Option<Instant>,
+	#[serde(skip)]
+	pub grace_timer: Option<SpawnHandle>,
 	#[serde(skip)]
 	pub cards: Vec<Card>,
+	/// `None` if the seat is played over HTTP
 	#[serde(skip)]
-	pub web_socket: Addr<WebSocket>,
+	pub web_socket: Option<Addr<WebSocket>>,
+	/// Authenticates HTTP requests for the seat
+	#[serde(skip)]
+	pub token: String,
+	#[serde(skip)]
+	pub outbox: RefCell<Outbox>,
+}
+
+/// Who is playing a seat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Control {
+	Human,
+	/// The seat is held for the player until the grace period runs out
+	Disconnected,
+	Bot,
+	/// The player left or got kicked: the bot plays the seat until someone takes it over
+	Vacant,
+}
+
+impl Control {
+	pub fn is_bot(self) -> bool {
+		matches!(self, Self::Bot | Self::Vacant)
+	}
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
 pub enum PlayerCardIdentifier {
 	CardPos(usize),
 	Card(Card),
 }
 
 impl Player {
-	pub fn new(username: String, web_socket: Addr<WebSocket>) -> Self {
+	pub fn new(
+		username: String,
+		account: Option<String>,
+		web_socket: Option<Addr<WebSocket>>,
+		encoding: Encoding,
+	) -> Self {
 		Self {
 			username,
+			account,
+			timeouts: 0,
+			control: Control::Human,
+			kick_votes: Vec::new(),
+			silenced: false,
+			muted: Vec::new(),
+			chat_times: Vec::new(),
+			last_reaction: None,
+			grace_timer: None,
 			cards: Vec::new(),
 			web_socket,
+			token: rand::thread_rng()
+				.sample_iter(&Alphanumeric)
+				.take(SEAT_TOKEN_LEN)
+				.map(char::from)
+				.collect(),
+			outbox: RefCell::new(Outbox::new(encoding)),
 		}
 	}
 
 	pub fn send<'a>(&self, msg: impl Borrow<ServerMessage<'a>>) -> crate::Result<()> {
-		self.web_socket
-			.do_send(crate::server::websocket::JsonifiedServerMessage(
-				msg.borrow().to_json_string(),
-			));
+		if self.control == Control::Vacant {
+			return Ok(());
+		}
+		let encoded = self.outbox.borrow_mut().push(msg.borrow());
+		if let Some(web_socket) = &self.web_socket {
+			web_socket.do_send(EncodedServerMessage(encoded));
+		}
 		Ok(())
 	}
 
+	/// Goes ahead of the numbered messages, so it doesn't get into the outbox
+	pub fn send_welcome(&self) {
+		if let Some(web_socket) = &self.web_socket {
+			let welcome = ServerMessage::Welcome {
+				protocol_version: PROTOCOL_VERSION,
+				features: PROTOCOL_FEATURES,
+				seat_token: &self.token,
+			};
+			web_socket.do_send(EncodedServerMessage(self.outbox.borrow().encoding().encode(&welcome)));
+		}
+	}
+
+	/// Sends again the messages that followed `last_seq`, false if some of them were lost
+	pub fn replay(&self, last_seq: u64) -> bool {
+		match (&self.web_socket, self.outbox.borrow().since(last_seq)) {
+			(Some(web_socket), Some(messages)) => {
+				for encoded in messages {
+					web_socket.do_send(EncodedServerMessage(encoded.clone()));
+				}
+				true
+			}
+			_ => false,
+		}
+	}
+
 	pub fn find_card(&self, card_identifier: PlayerCardIdentifier) -> Option<(usize, Card)> {
 		match card_identifier {
 			PlayerCardIdentifier::CardPos(pos) => self.cards.get(pos).map(|c| (pos, *c)),
@@ -44,7 +149,7 @@ impl Player {
 	}
 
 	pub fn team(player_id: usize) -> bool {
-		player_id % 2 != 0
+		!player_id.is_multiple_of(2)
 	}
 }
 
@@ -68,32 +173,23 @@ impl<G: Deref<Target = Game> + DerefMut<Target = Game>> DerefMut for PlayerPtr<G
 impl<G: Deref<Target = Game>> PlayerPtr<G> {
 	pub fn send_refresh_all(&self) -> crate::Result<()> {
 		self.send_game_state()?;
-		self.send_all_cards()?;
+		self.send_chat_history()?;
 		Ok(())
 	}
 
 	pub fn send_game_state(&self) -> crate::Result<()> {
-		self.send(ServerMessage::Game {
-			game: self.game.deref(),
-			player_id: self.player_id,
-		})?;
-		Ok(())
+		self.send(ServerMessage::Table(Box::new(self.game.view(self.player_id))))
 	}
 
-	pub fn send_all_cards(&self) -> crate::Result<()> {
-		for player in self.game.players() {
-			if player.player_id != self.player_id {
-				self.send(ServerMessage::CardCount {
-					player_id: player.player_id,
-					count: player.cards.len(),
-				})?;
-			}
-		}
-		self.send(ServerMessage::Cards {
-			player_id: self.player_id,
-			cards: self.cards.as_ref(),
-		})?;
-		Ok(())
+	pub fn send_chat_history(&self) -> crate::Result<()> {
+		self.send(ServerMessage::ChatHistory {
+			messages: self
+				.game
+				.chat_history
+				.iter()
+				.filter(|m| !self.muted.contains(&m.player_id))
+				.collect(),
+		})
 	}
 
 	pub fn send_player_bid(&self, player_bid: PlayerBid) -> crate::Result<()> {
@@ -103,6 +199,26 @@ impl<G: Deref<Target = Game>> PlayerPtr<G> {
 	pub fn team(&self) -> bool {
 		Player::team(self.player_id)
 	}
+
+	pub fn playable_cards(&self) -> impl Iterator<Item = Card> + '_ {
+		let running = match &self.game.game_state {
+			GameState::Running(running) => Some(running),
+			_ => None,
+		};
+		self.cards
+			.iter()
+			.copied()
+			.filter(move |&c| running.is_some_and(|r| r.can_play(&self.cards, c, self.team())))
+	}
+
+	/// Cheapest legal card, which is what gets played when the player runs out of time
+	pub fn lowest_playable_card(&self) -> Option<Card> {
+		let trump = match &self.game.game_state {
+			GameState::Running(running) => running.bid.trump,
+			_ => return None,
+		};
+		self.playable_cards().min_by(|a, b| a.cmp_points(*b, trump))
+	}
 }
 
 impl std::fmt::Debug for Player {
@@ -0,0 +1,424 @@
+use crate::prelude::*;
+
+use {
+	serde::{
+		ser::{self, Impossible, SerializeMap, SerializeStruct, SerializeStructVariant},
+		Serialize, Serializer,
+	},
+	std::{collections::VecDeque, marker::PhantomData},
+	tokio::sync::watch,
+};
+
+/// Messages kept for replay to a client that lost its connection
+const OUTBOX_LEN: usize = 200;
+
+/// Messages sent to a seat, numbered so that clients can notice they missed some
+///
+/// Numbers are per seat rather than per table: seats are sent different messages (their own hand and view,
+/// chat filtered by their mutes), so a table-wide number would leave gaps that clients couldn't tell from losses.
+pub struct Outbox {
+	next_seq: u64,
+	sent: VecDeque<(u64, EncodedMessage)>,
+	/// Of the player's connection
+	encoding: Encoding,
+	/// Latest `seq`, watched by HTTP clients waiting for messages
+	last_seq: watch::Sender<u64>,
+}
+
+/// `{"<variant>": <fields>, "seq": <seq>}`
+///
+/// Written out by hand: `#[serde(flatten)]` would route the fields of struct variants through serde's internal
+/// serializer, which claims to be human readable and so gets cards as maps even in MessagePack.
+struct Sequenced<'a, 'b> {
+	message: &'b ServerMessage<'a>,
+	seq: u64,
+}
+
+impl Serialize for Sequenced<'_, '_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(2))?;
+		self.message.serialize(VariantEntry {
+			map: &mut map,
+			message: self.message,
+		})?;
+		map.serialize_entry("seq", &self.seq)?;
+		map.end()
+	}
+}
+
+macro_rules! unsupported {
+	($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+		$(fn $method(self, $(_: $arg),*) -> Result<$ok, Self::Error> {
+			Err(ser::Error::custom("server messages are newtype or struct variants"))
+		})*
+	};
+}
+
+/// Writes a `ServerMessage` variant as a single entry of `map`
+struct VariantEntry<'m, 'a, 'b, M> {
+	map: &'m mut M,
+	message: &'b ServerMessage<'a>,
+}
+
+impl<M: SerializeMap> Serializer for VariantEntry<'_, '_, '_, M> {
+	type Ok = ();
+	type Error = M::Error;
+	type SerializeSeq = Impossible<(), M::Error>;
+	type SerializeTuple = Impossible<(), M::Error>;
+	type SerializeTupleStruct = Impossible<(), M::Error>;
+	type SerializeTupleVariant = Impossible<(), M::Error>;
+	type SerializeMap = Impossible<(), M::Error>;
+	type SerializeStruct = Impossible<(), M::Error>;
+	type SerializeStructVariant = SkipFields<M::Error>;
+
+	fn serialize_newtype_variant<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		value: &T,
+	) -> Result<(), M::Error> {
+		self.map.serialize_entry(variant, value)
+	}
+
+	/// Fields are written by serializing the message again, as a struct this time
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		_len: usize,
+	) -> Result<SkipFields<M::Error>, M::Error> {
+		self.map.serialize_entry(variant, &VariantFields(self.message))?;
+		Ok(SkipFields(PhantomData))
+	}
+
+	fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<(), M::Error> {
+		Err(ser::Error::custom("server messages are newtype or struct variants"))
+	}
+
+	fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, _value: &T) -> Result<(), M::Error> {
+		Err(ser::Error::custom("server messages are newtype or struct variants"))
+	}
+
+	unsupported! {
+		serialize_bool(bool) -> ();
+		serialize_i8(i8) -> ();
+		serialize_i16(i16) -> ();
+		serialize_i32(i32) -> ();
+		serialize_i64(i64) -> ();
+		serialize_u8(u8) -> ();
+		serialize_u16(u16) -> ();
+		serialize_u32(u32) -> ();
+		serialize_u64(u64) -> ();
+		serialize_f32(f32) -> ();
+		serialize_f64(f64) -> ();
+		serialize_char(char) -> ();
+		serialize_str(&str) -> ();
+		serialize_bytes(&[u8]) -> ();
+		serialize_none() -> ();
+		serialize_unit() -> ();
+		serialize_unit_struct(&'static str) -> ();
+		serialize_unit_variant(&'static str, u32, &'static str) -> ();
+		serialize_seq(Option<usize>) -> Self::SerializeSeq;
+		serialize_tuple(usize) -> Self::SerializeTuple;
+		serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+		serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+		serialize_map(Option<usize>) -> Self::SerializeMap;
+		serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+	}
+}
+
+/// Fields of a struct variant, already written by `VariantFields`
+struct SkipFields<E>(PhantomData<E>);
+
+impl<E: ser::Error> SerializeStructVariant for SkipFields<E> {
+	type Ok = ();
+	type Error = E;
+
+	fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, _value: &T) -> Result<(), E> {
+		Ok(())
+	}
+
+	fn end(self) -> Result<(), E> {
+		Ok(())
+	}
+}
+
+/// Fields of a struct variant of the message, as a struct
+struct VariantFields<'a, 'b>(&'b ServerMessage<'a>);
+
+impl Serialize for VariantFields<'_, '_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.0.serialize(StructVariantAsStruct(serializer))
+	}
+}
+
+struct StructVariantAsStruct<S>(S);
+
+impl<S: Serializer> Serializer for StructVariantAsStruct<S> {
+	type Ok = S::Ok;
+	type Error = S::Error;
+	type SerializeSeq = Impossible<S::Ok, S::Error>;
+	type SerializeTuple = Impossible<S::Ok, S::Error>;
+	type SerializeTupleStruct = Impossible<S::Ok, S::Error>;
+	type SerializeTupleVariant = Impossible<S::Ok, S::Error>;
+	type SerializeMap = Impossible<S::Ok, S::Error>;
+	type SerializeStruct = Impossible<S::Ok, S::Error>;
+	type SerializeStructVariant = StructFields<S::SerializeStruct>;
+
+	fn is_human_readable(&self) -> bool {
+		self.0.is_human_readable()
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<Self::SerializeStructVariant, S::Error> {
+		self.0.serialize_struct(variant, len).map(StructFields)
+	}
+
+	fn serialize_newtype_variant<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_value: &T,
+	) -> Result<S::Ok, S::Error> {
+		Err(ser::Error::custom("expected a struct variant"))
+	}
+
+	fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<S::Ok, S::Error> {
+		Err(ser::Error::custom("expected a struct variant"))
+	}
+
+	fn serialize_newtype_struct<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		_value: &T,
+	) -> Result<S::Ok, S::Error> {
+		Err(ser::Error::custom("expected a struct variant"))
+	}
+
+	unsupported! {
+		serialize_bool(bool) -> S::Ok;
+		serialize_i8(i8) -> S::Ok;
+		serialize_i16(i16) -> S::Ok;
+		serialize_i32(i32) -> S::Ok;
+		serialize_i64(i64) -> S::Ok;
+		serialize_u8(u8) -> S::Ok;
+		serialize_u16(u16) -> S::Ok;
+		serialize_u32(u32) -> S::Ok;
+		serialize_u64(u64) -> S::Ok;
+		serialize_f32(f32) -> S::Ok;
+		serialize_f64(f64) -> S::Ok;
+		serialize_char(char) -> S::Ok;
+		serialize_str(&str) -> S::Ok;
+		serialize_bytes(&[u8]) -> S::Ok;
+		serialize_none() -> S::Ok;
+		serialize_unit() -> S::Ok;
+		serialize_unit_struct(&'static str) -> S::Ok;
+		serialize_unit_variant(&'static str, u32, &'static str) -> S::Ok;
+		serialize_seq(Option<usize>) -> Self::SerializeSeq;
+		serialize_tuple(usize) -> Self::SerializeTuple;
+		serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+		serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+		serialize_map(Option<usize>) -> Self::SerializeMap;
+		serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+	}
+}
+
+struct StructFields<S>(S);
+
+impl<S: SerializeStruct> SerializeStructVariant for StructFields<S> {
+	type Ok = S::Ok;
+	type Error = S::Error;
+
+	fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), S::Error> {
+		self.0.serialize_field(key, value)
+	}
+
+	fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+		self.0.skip_field(key)
+	}
+
+	fn end(self) -> Result<S::Ok, S::Error> {
+		self.0.end()
+	}
+}
+
+impl Outbox {
+	pub fn new(encoding: Encoding) -> Self {
+		Self {
+			next_seq: 0,
+			sent: VecDeque::new(),
+			encoding,
+			last_seq: watch::channel(0).0,
+		}
+	}
+
+	pub fn encoding(&self) -> Encoding {
+		self.encoding
+	}
+
+	/// Kept messages can't be replayed to a connection using another encoding
+	pub fn set_encoding(&mut self, encoding: Encoding) {
+		if encoding != self.encoding {
+			self.encoding = encoding;
+			self.sent.clear();
+		}
+	}
+
+	/// Numbers and keeps the message, returning what should be sent
+	pub fn push(&mut self, message: &ServerMessage) -> EncodedMessage {
+		self.next_seq += 1;
+		let encoded = self.encoding.encode(&Sequenced {
+			message,
+			seq: self.next_seq,
+		});
+		if self.sent.len() >= OUTBOX_LEN {
+			self.sent.pop_front();
+		}
+		self.sent.push_back((self.next_seq, encoded.clone()));
+		self.last_seq.send_replace(self.next_seq);
+		encoded
+	}
+
+	pub fn last_seq(&self) -> u64 {
+		self.next_seq
+	}
+
+	/// Notified each time a message is pushed
+	pub fn subscribe(&self) -> watch::Receiver<u64> {
+		self.last_seq.subscribe()
+	}
+
+	/// Messages that followed `last_seq`, `None` if some of them aren't kept anymore
+	///
+	/// `last_seq` comes from clients: anything past what was sent also gets `None`, for a full refresh.
+	pub fn since(&self, last_seq: u64) -> Option<impl Iterator<Item = &EncodedMessage>> {
+		let first_kept_seq = self.next_seq + 1 - self.sent.len() as u64;
+		match (first_kept_seq..=self.next_seq + 1).contains(&last_seq.checked_add(1)?) {
+			true => Some(
+				self.sent
+					.iter()
+					.filter(move |(seq, _)| *seq > last_seq)
+					.map(|(_, encoded)| encoded),
+			),
+			false => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn outbox_with(count: u64) -> Outbox {
+		let mut outbox = Outbox::new(Encoding::Json);
+		for winner_id in 0..count {
+			outbox.push(&ServerMessage::Trick {
+				winner_id: winner_id as usize,
+			});
+		}
+		outbox
+	}
+
+	fn seqs<'a>(messages: impl Iterator<Item = &'a EncodedMessage>) -> Vec<u64> {
+		messages
+			.map(|encoded| match encoded {
+				EncodedMessage::Text(json) => serde_json::from_str::<serde_json::Value>(json).unwrap()["seq"]
+					.as_u64()
+					.unwrap(),
+				EncodedMessage::Binary(_) => unreachable!(),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn since_replays_missed_messages() {
+		let outbox = outbox_with(5);
+		assert_eq!(seqs(outbox.since(2).unwrap()), [3, 4, 5]);
+		assert_eq!(seqs(outbox.since(5).unwrap()), Vec::<u64>::new());
+		assert_eq!(seqs(outbox.since(0).unwrap()), [1, 2, 3, 4, 5]);
+	}
+
+	#[test]
+	fn since_gives_up_on_dropped_messages() {
+		let outbox = outbox_with(OUTBOX_LEN as u64 + 10);
+		assert!(outbox.since(5).is_none());
+		assert!(outbox.since(10).is_some());
+	}
+
+	#[test]
+	fn since_rejects_unknown_seqs() {
+		let outbox = outbox_with(3);
+		assert!(outbox.since(4).is_none());
+		assert!(outbox.since(u64::MAX).is_none());
+	}
+
+	fn played_card(card: Card) -> ServerMessage<'static> {
+		ServerMessage::PlayedCard {
+			player_id: 1,
+			card_pos: 2,
+			card,
+			belote_rebelote: None,
+		}
+	}
+
+	#[test]
+	fn json_messages_carry_their_seq() {
+		let mut outbox = Outbox::new(Encoding::Json);
+		let card = "Jh".parse().unwrap();
+		match outbox.push(&played_card(card)) {
+			EncodedMessage::Text(json) => assert_eq!(
+				serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+				serde_json::json!({
+					"PlayedCard": {
+						"player_id": 1,
+						"card_pos": 2,
+						"card": {"value": "Jack", "suit": "Hearts"},
+						"belote_rebelote": null,
+					},
+					"seq": 1,
+				})
+			),
+			EncodedMessage::Binary(_) => unreachable!(),
+		}
+	}
+
+	#[test]
+	fn msgpack_messages_send_cards_as_bytes() {
+		let mut outbox = Outbox::new(Encoding::MsgPack);
+		let card: Card = "Jh".parse().unwrap();
+		let error = GameError::IllegalCard {
+			reason: IllegalPlay::MustTrump,
+		};
+		let messages = [
+			played_card(card),
+			ServerMessage::Error {
+				error: &error,
+				message: error.to_string(),
+				request_id: Some(3),
+			},
+		];
+		let decoded: Vec<serde_json::Value> = messages
+			.iter()
+			.map(|message| match outbox.push(message) {
+				EncodedMessage::Binary(bytes) => rmp_serde::from_slice(&bytes).unwrap(),
+				EncodedMessage::Text(_) => unreachable!(),
+			})
+			.collect();
+		let byte = decoded[0]["PlayedCard"]["card"].as_u64().unwrap();
+		assert_eq!(Card::from_byte(byte.try_into().unwrap()), Some(card));
+		assert_eq!(decoded[0]["PlayedCard"]["card_pos"], 2);
+		assert_eq!(decoded[0]["seq"], 1);
+		assert_eq!(decoded[1]["Error"]["code"], "IllegalCard");
+		assert_eq!(decoded[1]["Error"]["params"]["reason"], "MustTrump");
+		assert_eq!(decoded[1]["Error"]["request_id"], 3);
+		assert_eq!(decoded[1]["seq"], 2);
+	}
+}
@@ -0,0 +1,524 @@
+// Coinche protocol version 2, generated by the server
+
+/** Client message the server would accept from the viewer */
+export type Action = "Pass" | "Coinche" | "Claim" | "AnswerClaim" | "RequestUndo" | "AnswerUndo" | "RequestRedeal" | "Resume" | {
+	Bid: {
+		above?: BidScore | null;
+	};
+} | "SurCoinche" | {
+	PlayCard: {
+		cards: Card[];
+	};
+};
+
+export type BeloteRebelote = "Belote" | "Rebelote";
+
+export type Bid = {
+	score: BidScore;
+	trump: Trump;
+};
+
+export type BidScore = "80" | "90" | "100" | "110" | "120" | "130" | "140" | "150" | "160" | "170" | "180" | "Capot";
+
+export type Board = {
+	cards: Card[];
+	starting_player_id: number;
+};
+
+/** An unnamed tuple with Value and Suit.  Serialized as its `Deck` byte by binary formats. */
+export type Card = {
+	suit: Suit;
+	value: Value;
+};
+
+export type ChatMessage = {
+	player_id: number;
+	text: string;
+	/** Unix timestamp (ms) */
+	timestamp: number;
+	username: string;
+};
+
+export type Claim = {
+	/** Opponents who accepted the claim so far */
+	accepted_by: number[];
+	player_id: number;
+};
+
+export type ClientMessage = "RefreshGameState" | "Coinche" | "Pause" | "Restart" | {
+	Init: {
+		/** `seq` of the last message received before losing the connection, to only get the ones missed */
+		last_seq?: number | null;
+		/** Required to join a locked table */
+		password?: string | null;
+		/** Clients predating versioning speak the first version */
+		protocol_version?: number;
+		/** Given in `Welcome`, lets guests take their seat back after losing the connection */
+		seat_token?: string | null;
+		username: string;
+	};
+} | {
+	Replay: {
+		last_seq: number;
+	};
+} | {
+	Bid: Bid | null;
+} | {
+	SurCoinche: boolean;
+} | {
+	PlayCard: PlayerCardIdentifier;
+} | {
+	Chat: string;
+} | {
+	React: Reaction;
+} | {
+	Mute: {
+		muted: boolean;
+		player_id: number;
+	};
+} | "Claim" | {
+	AnswerClaim: boolean;
+} | "RequestUndo" | {
+	AnswerUndo: boolean;
+} | "RequestRedeal" | "Resume" | "Leave" | {
+	VoteKick: {
+		player_id: number;
+	};
+} | {
+	SetSettings: Settings;
+} | {
+	SetPassword: string | null;
+} | {
+	ReorderSeats: number[];
+} | {
+	Silence: {
+		player_id: number;
+		silenced: boolean;
+	};
+} | {
+	TransferHost: {
+		player_id: number;
+	};
+};
+
+/** A `ClientMessage`, which may be sent as `{"request_id": 1, "message": ...}` to get an `Ack` or an `Error` carrying the same id in response */
+export type ClientRequest = {
+	message: ClientMessage;
+	request_id?: number | null;
+};
+
+export type CoincheState = "No" | {
+	Coinche: {
+		player_id: number;
+	};
+} | {
+	Surcoinche: {
+		coincher_id: number;
+		surcoincher_id: number;
+	};
+};
+
+/** Who is playing a seat */
+export type Control = "Human" | "Bot" | "Disconnected" | "Vacant";
+
+/** Deals are drawn from a seed shared with other tables, so that the same hands get played at each of them */
+export type Duplicate = {
+	/** Number of deals in the match */
+	boards: number;
+	/** Points of each seat team on each board played so far, `[0, 0]` if nobody bid */
+	results: number[][];
+	/** Hands are moved one seat over: East/West hold what North/South hold at the other tables */
+	swapped: boolean;
+};
+
+/** Which rule forbids playing a card */
+export type IllegalPlay = "MustFollowSuit" | "MustTrump" | "MustOvertrump";
+
+/** Hands that get the cards dealt again automatically */
+export type Misdeal = "NoAce" | "NoFaceCard";
+
+/** Layered over the game state: gameplay actions and timers are frozen while paused */
+export type Pause = {
+	player_id: number;
+	/** Players who agreed to resume so far */
+	resume_votes: number[];
+};
+
+export type Phase = "Bidding" | "Running";
+
+export type PhaseView = "Lobby" | {
+	Bidding: {
+		bids: PlayerBid[];
+		/** Player who coinched, if any */
+		coincher_id?: number | null;
+	};
+} | {
+	Playing: {
+		belote_player?: number | null;
+		bid: Bid;
+		bidder_id: number;
+		/** Cards of the trick being played */
+		board: Board;
+		claim?: Claim | null;
+		coinche_state: CoincheState;
+		last_trick?: Trick | null;
+		/** Number of tricks taken by each team */
+		tricks: number[];
+		undo?: UndoRequest | null;
+	};
+};
+
+export type PlayerBid = {
+	bid?: Bid | null;
+	player_id: number;
+};
+
+export type PlayerCardIdentifier = {
+	CardPos: number;
+} | {
+	Card: Card;
+};
+
+/** Predefined reactions, shown as a transient bubble next to the seat */
+export type Reaction = "Laugh" | "Angry" | "ThumbsUp" | "WellPlayed" | "Oops" | "Thanks" | "Hurry";
+
+export type RoundPoints = {
+	bid: Bid;
+	points: number[];
+	scored_points: number[];
+	team: boolean;
+};
+
+export type SeatView = {
+	account?: string | null;
+	card_count: number;
+	control: Control;
+	kick_votes: number[];
+	silenced: boolean;
+	timeouts: number;
+	username: string;
+};
+
+/** How every `ServerMessage` sent by the game is actually serialized */
+export type SequencedServerMessage = ServerMessage & {
+	/** Increases by one with each message sent to the seat */
+	seq: number;
+};
+
+export type ServerMessage = {
+	Welcome: {
+		features: string[];
+		protocol_version: number;
+		seat_token: string;
+	};
+} | {
+	Table: TableView;
+} | {
+	PlayerBid: PlayerBid;
+} | {
+	Coinche: {
+		player_id: number;
+	};
+} | {
+	SurCoinche: {
+		player_id: number;
+	};
+} | {
+	PlayedCard: {
+		belote_rebelote?: BeloteRebelote | null;
+		card: Card;
+		card_pos: number;
+		player_id: number;
+	};
+} | {
+	Trick: {
+		winner_id: number;
+	};
+} | {
+	Control: {
+		control: Control;
+		player_id: number;
+	};
+} | {
+	KickVotes: {
+		player_id: number;
+		votes: number[];
+	};
+} | {
+	Chat: ChatMessage;
+} | {
+	ChatHistory: {
+		messages: ChatMessage[];
+	};
+} | {
+	Silenced: {
+		player_id: number;
+		silenced: boolean;
+	};
+} | {
+	Reaction: {
+		player_id: number;
+		reaction: Reaction;
+	};
+} | {
+	MatchEnd: {
+		points: number[];
+		/** `None` on a tie */
+		winning_team?: boolean | null;
+	};
+} | {
+	Claim: {
+		cards: Card[];
+		/** The claim couldn't be checked by the server: opponents have to accept it */
+		pending: boolean;
+		player_id: number;
+	};
+} | {
+	ClaimResult: {
+		accepted: boolean;
+		player_id: number;
+	};
+} | {
+	UndoRequest: {
+		player_id: number;
+	};
+} | {
+	UndoResult: {
+		accepted: boolean;
+		player_id: number;
+	};
+} | {
+	RedealVotes: {
+		votes: number[];
+	};
+} | {
+	Misdeal: {
+		misdeal: Misdeal;
+		player_id: number;
+		/** Still a misdeal after too many deals: the hand is played anyway */
+		waived: boolean;
+	};
+} | {
+	Host: {
+		player_id: number;
+	};
+} | {
+	Pause: {
+		pause?: Pause | null;
+	};
+} | {
+	TurnDeadline: TurnDeadline;
+} | {
+	Ack: {
+		request_id: number;
+	};
+} | {
+	/** Why a client message was turned down  Serialized as a stable `code` (the variant name) along with its `params`, if any */
+	Error: {
+		/** Human readable, for display */
+		message: string;
+		request_id?: number | null;
+	} & ({
+		code: "InvalidMessage";
+		params: {
+			details: string;
+		};
+	} | {
+		code: "UnsupportedProtocol";
+		params: {
+			protocol_version: number;
+		};
+	} | {
+		code: "NotInitialized";
+	} | {
+		code: "AlreadyInitialized";
+	} | {
+		code: "InvalidSeatToken";
+	} | {
+		code: "WrongPassword";
+	} | {
+		code: "UsernameTaken";
+	} | {
+		code: "TableReserved";
+	} | {
+		code: "TableFull";
+	} | {
+		code: "WrongPhase";
+		params: {
+			expected: Phase;
+		};
+	} | {
+		code: "Paused";
+	} | {
+		code: "NotYourTurn";
+	} | {
+		code: "BidTooLow";
+		params: {
+			previous: BidScore;
+		};
+	} | {
+		code: "AlreadyCoinched";
+	} | {
+		code: "NoOpponentBid";
+	} | {
+		code: "NotCoinched";
+	} | {
+		code: "NotCoinchedTeam";
+	} | {
+		code: "InvalidCard";
+	} | {
+		code: "IllegalCard";
+		params: {
+			reason: IllegalPlay;
+		};
+	} | {
+		code: "ClaimPending";
+	} | {
+		code: "UndoPending";
+	} | {
+		code: "NoPendingClaim";
+	} | {
+		code: "NotOpponent";
+	} | {
+		code: "ClaimRefuted";
+	} | {
+		code: "NoPendingUndo";
+	} | {
+		code: "NothingToUndo";
+	} | {
+		code: "OwnRequest";
+	} | {
+		code: "PredefinedDeals";
+	} | {
+		code: "AlreadyPaused";
+	} | {
+		code: "NotPaused";
+	} | {
+		code: "NotHost";
+	} | {
+		code: "MatchStarted";
+	} | {
+		code: "SeatsReserved";
+	} | {
+		code: "InvalidSeatOrder";
+	} | {
+		code: "InvalidPlayer";
+		params: {
+			player_id: number;
+		};
+	} | {
+		code: "PlayerNotPresent";
+		params: {
+			player_id: number;
+		};
+	} | {
+		code: "PlayerPresent";
+		params: {
+			player_id: number;
+		};
+	} | {
+		code: "SeatVacant";
+		params: {
+			player_id: number;
+		};
+	} | {
+		code: "KickSelf";
+	} | {
+		code: "EmptyMessage";
+	} | {
+		code: "MessageTooLong";
+		params: {
+			max_len: number;
+		};
+	} | {
+		code: "TooManyMessages";
+	} | {
+		code: "Silenced";
+	} | {
+		code: "Internal";
+	});
+};
+
+export type Settings = {
+	/** Seconds a player has to bid (or surcoincher) before the server passes for them */
+	bid_timeout_secs?: number | null;
+	/** Words masked in chat messages */
+	chat_word_filter?: string[];
+	/** Seconds a disconnected player's seat is held before a bot takes over, `None` to wait for them forever */
+	disconnect_grace_secs?: number | null;
+	/** Points to reach for winning the match, scores are kept going forever if unset */
+	match_points?: number | null;
+	/** Hands for which cards are dealt again */
+	misdeals?: Misdeal[];
+	/** Seconds a player has to play before the server plays their lowest legal card */
+	play_timeout_secs?: number | null;
+	/** Seconds the other players have to accept taking back a card */
+	undo_window_secs?: number;
+};
+
+export type Suit = "Spades" | "Hearts" | "Diamonds" | "Clubs";
+
+/** Everything a seat may see of the table, so that clients can render from this alone */
+export type TableView = {
+	deal_count: number;
+	dealer_id: number;
+	duplicate?: Duplicate | null;
+	/** The viewer's cards */
+	hand: Card[];
+	host_id?: number | null;
+	/** What the viewer may do right now */
+	legal_actions: Action[];
+	locked: boolean;
+	pause?: Pause | null;
+	phase: PhaseView;
+	/** Seat of the viewer */
+	player_id: number;
+	players: SeatView[];
+	points: number[];
+	redeal_votes: number[];
+	round_points: RoundPoints[];
+	settings: Settings;
+	turn?: Turn | null;
+	turn_deadline?: TurnDeadline | null;
+};
+
+export type Trick = {
+	cards: Card[];
+	starting_player_id: number;
+	winner_id: number;
+};
+
+export type Trump = "NoTrump" | "AllTrump" | {
+	Suit: Suit;
+};
+
+/** The action the game is currently waiting for */
+export type Turn = {
+	Bid: {
+		player_id: number;
+	};
+} | {
+	SurCoinche: {
+		team: boolean;
+	};
+} | {
+	PlayCard: {
+		player_id: number;
+	};
+};
+
+export type TurnDeadline = {
+	/** Unix timestamp (ms) after which the server acts on behalf of the player */
+	deadline: number;
+	turn: Turn;
+};
+
+export type UndoRequest = {
+	/** Players who accepted the take-back so far */
+	accepted_by: number[];
+	/** Unix timestamp (ms) after which the request is turned down */
+	expires_at: number;
+	player_id: number;
+};
+
+export type Value = "Seven" | "Eight" | "Nine" | "Jack" | "Queen" | "King" | "Ten" | "Ace";
@@ -0,0 +1,210 @@
+use crate::messages::{ClientMessage, ClientRequest, ServerMessage, PROTOCOL_VERSION};
+
+use schemars::{
+	gen::SchemaSettings,
+	schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec},
+};
+
+/// JSON Schema of every message that goes through the websocket, and of the types they're made of
+pub fn json_schema() -> RootSchema {
+	let mut generator = SchemaSettings::draft07().into_generator();
+	// Adds them to the definitions
+	generator.subschema_for::<ClientRequest>();
+	generator.subschema_for::<ClientMessage>();
+	generator.subschema_for::<ServerMessage>();
+	let mut definitions = generator.take_definitions();
+	definitions.insert(
+		"SequencedServerMessage".to_owned(),
+		serde_json::from_value(serde_json::json!({
+			"description": "How every `ServerMessage` sent by the game is actually serialized",
+			"allOf": [
+				{ "$ref": "#/definitions/ServerMessage" },
+				{
+					"type": "object",
+					"required": ["seq"],
+					"properties": {
+						"seq": {
+							"description": "Increases by one with each message sent to the seat",
+							"type": "integer",
+							"format": "uint64",
+							"minimum": 0,
+						},
+					},
+				},
+			],
+		}))
+		.unwrap(),
+	);
+	let mut schema = SchemaObject::default();
+	let metadata = schema.metadata();
+	metadata.title = Some("Coinche protocol".to_owned());
+	metadata.description = Some(format!("Version {}", PROTOCOL_VERSION));
+	RootSchema {
+		meta_schema: generator.settings().meta_schema.clone(),
+		schema,
+		definitions,
+	}
+}
+
+/// TypeScript definitions of the same types, derived from their JSON Schema
+pub fn typescript() -> String {
+	let mut ts = format!(
+		"// Coinche protocol version {}, generated by the server\n",
+		PROTOCOL_VERSION
+	);
+	for (name, schema) in json_schema().definitions.iter() {
+		ts.push('\n');
+		ts.push_str(&doc_comment(schema, ""));
+		ts.push_str(&format!("export type {} = {};\n", name, ts_type(schema, "")));
+	}
+	ts
+}
+
+fn doc_comment(schema: &Schema, indent: &str) -> String {
+	let description = match schema {
+		Schema::Object(SchemaObject {
+			metadata: Some(metadata),
+			..
+		}) => metadata.description.as_deref(),
+		_ => None,
+	};
+	match description {
+		Some(description) => format!("{}/** {} */\n", indent, description.replace('\n', " ")),
+		None => String::new(),
+	}
+}
+
+fn ts_type(schema: &Schema, indent: &str) -> String {
+	let schema = match schema {
+		Schema::Bool(true) => return "unknown".to_owned(),
+		Schema::Bool(false) => return "never".to_owned(),
+		Schema::Object(schema) => schema,
+	};
+	if let Some(reference) = &schema.reference {
+		return reference.trim_start_matches("#/definitions/").to_owned();
+	}
+	// All of these apply
+	let mut types = Vec::new();
+	if let Some(values) = &schema.enum_values {
+		types.push(union(values.iter().map(|v| v.to_string())));
+	} else if let Some(instance_type) = &schema.instance_type {
+		let instance_types = match instance_type {
+			SingleOrVec::Single(instance_type) => std::slice::from_ref(instance_type.as_ref()),
+			SingleOrVec::Vec(instance_types) => instance_types.as_slice(),
+		};
+		types.push(union(
+			instance_types.iter().map(|t| instance_ts_type(schema, t, indent)),
+		));
+	}
+	if let Some(subschemas) = &schema.subschemas {
+		for schema in subschemas.all_of.iter().flatten() {
+			types.push(ts_type(schema, indent));
+		}
+		for alternatives in [&subschemas.one_of, &subschemas.any_of].into_iter().flatten() {
+			types.push(union(alternatives.iter().map(|s| ts_type(s, indent))));
+		}
+	}
+	match types.len() {
+		0 => "unknown".to_owned(),
+		1 => types.pop().unwrap(),
+		_ => types.iter().map(|t| parenthesized(t)).collect::<Vec<_>>().join(" & "),
+	}
+}
+
+fn instance_ts_type(schema: &SchemaObject, instance_type: &InstanceType, indent: &str) -> String {
+	match instance_type {
+		InstanceType::Null => "null".to_owned(),
+		InstanceType::Boolean => "boolean".to_owned(),
+		InstanceType::Integer | InstanceType::Number => "number".to_owned(),
+		InstanceType::String => "string".to_owned(),
+		InstanceType::Array => match schema.array.as_ref().and_then(|a| a.items.as_ref()) {
+			Some(SingleOrVec::Single(item)) => format!("{}[]", parenthesized(&ts_type(item, indent))),
+			Some(SingleOrVec::Vec(items)) => {
+				let items: Vec<String> = items.iter().map(|item| ts_type(item, indent)).collect();
+				format!("[{}]", items.join(", "))
+			}
+			None => "unknown[]".to_owned(),
+		},
+		InstanceType::Object => {
+			let object = match &schema.object {
+				Some(object) if !object.properties.is_empty() => object,
+				Some(object) => match &object.additional_properties {
+					Some(values) => return format!("Record<string, {}>", ts_type(values, indent)),
+					None => return "Record<string, unknown>".to_owned(),
+				},
+				None => return "Record<string, unknown>".to_owned(),
+			};
+			let field_indent = format!("{}\t", indent);
+			let mut ts = "{\n".to_owned();
+			for (name, property) in object.properties.iter() {
+				let optional = if object.required.contains(name) { "" } else { "?" };
+				ts.push_str(&doc_comment(property, &field_indent));
+				ts.push_str(&format!(
+					"{}{}{}: {};\n",
+					field_indent,
+					name,
+					optional,
+					ts_type(property, &field_indent)
+				));
+			}
+			ts.push_str(indent);
+			ts.push('}');
+			ts
+		}
+	}
+}
+
+fn union(types: impl Iterator<Item = String>) -> String {
+	types.collect::<Vec<_>>().join(" | ")
+}
+
+/// Wrapped in parentheses if it's a union or intersection of types
+fn parenthesized(ts_type: &str) -> String {
+	let mut depth = 0;
+	let mut compound = false;
+	for (i, c) in ts_type.char_indices() {
+		match c {
+			'{' | '(' | '[' | '<' => depth += 1,
+			'}' | ')' | ']' | '>' => depth -= 1,
+			'|' | '&' if depth == 0 && ts_type[..i].ends_with(' ') => compound = true,
+			_ => (),
+		}
+	}
+	match compound {
+		true => format!("({})", ts_type),
+		false => ts_type.to_owned(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::{env, fs, path::Path};
+
+	/// Compares with the copy committed next to this file, which `UPDATE_SNAPSHOTS=1 cargo test` writes again
+	fn check_snapshot(file_name: &str, generated: &str) {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src").join(file_name);
+		if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+			fs::write(&path, generated).unwrap();
+		}
+		let committed = fs::read_to_string(&path).unwrap_or_default();
+		assert!(
+			committed == generated,
+			"{} is out of date: run `UPDATE_SNAPSHOTS=1 cargo test` and review the changes to the protocol",
+			file_name
+		);
+	}
+
+	#[test]
+	fn json_schema_matches_snapshot() {
+		let mut schema = serde_json::to_string_pretty(&json_schema()).unwrap();
+		schema.push('\n');
+		check_snapshot("protocol.schema.json", &schema);
+	}
+
+	#[test]
+	fn typescript_matches_snapshot() {
+		check_snapshot("protocol.d.ts", &typescript());
+	}
+}
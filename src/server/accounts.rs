@@ -0,0 +1,220 @@
+use crate::{
+	accounts::{
+		rating::GetLeaderboard,
+		stats::{GetStats, PlayerStats},
+		*,
+	},
+	prelude::*,
+};
+
+use {
+	actix_web::{
+		cookie::{time, Cookie, SameSite},
+		http::StatusCode,
+		web, HttpRequest, HttpResponse,
+	},
+	argon2::{
+		password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+		Argon2,
+	},
+	std::sync::OnceLock,
+};
+
+pub const SESSION_COOKIE: &str = "session";
+
+#[derive(Deserialize)]
+pub struct RegisterForm {
+	username: String,
+	/// Defaults to the username
+	display_name: Option<String>,
+	password: String,
+}
+
+#[derive(Deserialize)]
+pub struct LoginForm {
+	username: String,
+	password: String,
+}
+
+#[derive(Serialize)]
+struct StatsResponse {
+	#[serde(flatten)]
+	stats: PlayerStats,
+	average_points_per_deal: f64,
+	match_win_rate: Option<f64>,
+}
+
+pub fn error_response(status: StatusCode, message: &str) -> HttpResponse {
+	HttpResponse::build(status).json(serde_json::json!({ "message": message }))
+}
+
+fn session_cookie(token: String) -> Cookie<'static> {
+	Cookie::build(SESSION_COOKIE, token)
+		.path("/")
+		.http_only(true)
+		.secure(true)
+		.same_site(SameSite::Lax)
+		.max_age(time::Duration::seconds(SESSION_DURATION.as_secs() as i64))
+		.finish()
+}
+
+pub async fn register(form: web::Json<RegisterForm>, accounts: web::Data<Addr<Accounts>>) -> HttpResponse {
+	let RegisterForm {
+		username,
+		display_name,
+		password,
+	} = form.into_inner();
+	if password.chars().count() < MIN_PASSWORD_LEN {
+		return error_response(
+			StatusCode::BAD_REQUEST,
+			&format!("Password must be at least {} characters long", MIN_PASSWORD_LEN),
+		);
+	}
+	// Hashing is purposely slow: keep it off the actors' thread
+	let password_hash = match web::block(move || {
+		Argon2::default()
+			.hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+			.map(|h| h.to_string())
+	})
+	.await
+	{
+		Ok(Ok(hash)) => hash,
+		_ => return HttpResponse::InternalServerError().finish(),
+	};
+	let register = Register {
+		display_name: display_name.unwrap_or_else(|| username.clone()),
+		username,
+		password_hash,
+	};
+	let identity = match accounts.send(register).await {
+		Ok(Ok(identity)) => identity,
+		Ok(Err(err)) => return error_response(StatusCode::BAD_REQUEST, &err.to_string()),
+		Err(_) => return HttpResponse::InternalServerError().finish(),
+	};
+	open_session(identity.username, &accounts).await
+}
+
+/// Checked against when the account doesn't exist, so that the answer takes just as long
+fn dummy_password_hash() -> &'static str {
+	static HASH: OnceLock<String> = OnceLock::new();
+	HASH.get_or_init(|| {
+		Argon2::default()
+			.hash_password(b"", &SaltString::generate(&mut OsRng))
+			.expect("Hashing with the default parameters should work")
+			.to_string()
+	})
+}
+
+/// Whether the server runs behind a proxy of ours, which appends the client's address to `X-Forwarded-For`
+#[derive(Debug, Clone, Copy)]
+pub struct BehindProxy(pub bool);
+
+/// Address of the client, for throttling. Clients can send `X-Forwarded-For` themselves:
+/// only the address appended by our own proxy is trusted.
+fn client_address(req: &HttpRequest, behind_proxy: BehindProxy) -> String {
+	let forwarded_for = req
+		.headers()
+		.get_all("x-forwarded-for")
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(|value| value.split(','))
+		.last()
+		.map(|address| address.trim().to_owned());
+	match (behind_proxy.0, forwarded_for) {
+		(true, Some(address)) => address,
+		_ => req.peer_addr().map(|a| a.ip().to_string()).unwrap_or_default(),
+	}
+}
+
+pub async fn login(
+	req: HttpRequest,
+	form: web::Json<LoginForm>,
+	accounts: web::Data<Addr<Accounts>>,
+	behind_proxy: web::Data<BehindProxy>,
+) -> HttpResponse {
+	let LoginForm { username, password } = form.into_inner();
+	let address = client_address(&req, **behind_proxy);
+	let password_hash = match accounts
+		.send(GetPasswordHash {
+			username: username.clone(),
+			address: address.clone(),
+		})
+		.await
+	{
+		Ok(Ok(hash)) => hash,
+		Ok(Err(err)) => return error_response(StatusCode::TOO_MANY_REQUESTS, &err.to_string()),
+		Err(_) => return HttpResponse::InternalServerError().finish(),
+	};
+	let valid = web::block(move || {
+		let known = password_hash.is_some();
+		let hash = password_hash.as_deref().unwrap_or_else(|| dummy_password_hash());
+		let verified =
+			PasswordHash::new(hash).is_ok_and(|h| Argon2::default().verify_password(password.as_bytes(), &h).is_ok());
+		known && verified
+	})
+	.await
+	.unwrap_or(false);
+	if !valid {
+		return error_response(StatusCode::UNAUTHORIZED, "Wrong username or password");
+	}
+	accounts.do_send(LoginSucceeded {
+		username: username.clone(),
+		address,
+	});
+	open_session(username, &accounts).await
+}
+
+async fn open_session(username: String, accounts: &Addr<Accounts>) -> HttpResponse {
+	match accounts.send(OpenSession { username }).await {
+		Ok(Ok((token, identity))) => HttpResponse::Ok().cookie(session_cookie(token)).json(identity),
+		_ => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+pub async fn logout(req: HttpRequest, accounts: web::Data<Addr<Accounts>>) -> HttpResponse {
+	if let Some(cookie) = req.cookie(SESSION_COOKIE) {
+		accounts.do_send(CloseSession {
+			token: cookie.value().to_owned(),
+		});
+	}
+	let mut removal = session_cookie(String::new());
+	removal.make_removal();
+	HttpResponse::Ok().cookie(removal).finish()
+}
+
+pub async fn me(req: HttpRequest, accounts: web::Data<Addr<Accounts>>) -> HttpResponse {
+	match authenticate(&req, &accounts).await {
+		Some(identity) => HttpResponse::Ok().json(identity),
+		None => error_response(StatusCode::UNAUTHORIZED, "Not logged in"),
+	}
+}
+
+/// Identity of the account whose session cookie came with the request
+pub async fn authenticate(req: &HttpRequest, accounts: &Addr<Accounts>) -> Option<Identity> {
+	let token = req.cookie(SESSION_COOKIE)?.value().to_owned();
+	accounts.send(Authenticate { token }).await.ok().flatten()
+}
+
+pub async fn stats(username: web::Path<String>, accounts: web::Data<Addr<Accounts>>) -> HttpResponse {
+	let stats = match accounts
+		.send(GetStats {
+			username: username.into_inner(),
+		})
+		.await
+	{
+		Ok(Some(stats)) => stats,
+		Ok(None) => return error_response(StatusCode::NOT_FOUND, "Unknown account"),
+		Err(_) => return HttpResponse::InternalServerError().finish(),
+	};
+	HttpResponse::Ok().json(StatsResponse {
+		average_points_per_deal: stats.average_points_per_deal(),
+		match_win_rate: stats.match_win_rate(),
+		stats,
+	})
+}
+
+pub async fn leaderboard(accounts: web::Data<Addr<Accounts>>) -> HttpResponse {
+	match accounts.send(GetLeaderboard).await {
+		Ok(leaderboard) => HttpResponse::Ok().json(leaderboard),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
@@ -1,6 +1,17 @@
 use crate::prelude::*;
 
-use actix::prelude::*;
+use {
+	crate::{
+		messages::PROTOCOL_VERSION,
+		notifier::{NotifiedAction, TurnNotification},
+	},
+	actix::prelude::*,
+	std::time::Duration,
+	tokio::sync::watch,
+};
+
+/// Lets bot moves be followed by the other players
+const BOT_DELAY: Duration = Duration::from_secs(1);
 
 impl Actor for Game {
 	type Context = Context<Self>;
@@ -17,16 +28,326 @@ impl Actor for Game {
 pub struct ClientGameMessage {
 	pub message: ClientMessage,
 	pub player_id: Option<usize>,
+	pub identity: Option<Identity>,
 	pub web_socket: Addr<WebSocket>,
+	pub encoding: Encoding,
 }
 impl Message for ClientGameMessage {
-	type Result = Result<Option<usize>, failure::Error>;
+	type Result = GameResult<SeatChange>;
 }
 
 impl Handler<ClientGameMessage> for Game {
-	type Result = Result<Option<usize>, failure::Error>;
+	type Result = GameResult<SeatChange>;
+
+	fn handle(&mut self, msg: ClientGameMessage, ctx: &mut Context<Self>) -> Self::Result {
+		self.handle_client_msg(
+			msg.player_id,
+			msg.message,
+			msg.identity,
+			Some(msg.web_socket),
+			msg.encoding,
+			ctx,
+		)
+	}
+}
+
+/// Takes a seat to play it over HTTP, returning the seat id and its token
+pub struct HttpJoin {
+	pub username: String,
+	pub password: Option<String>,
+	pub seat_token: Option<String>,
+	pub identity: Option<Identity>,
+}
+impl Message for HttpJoin {
+	type Result = GameResult<(usize, String)>;
+}
+
+impl Handler<HttpJoin> for Game {
+	type Result = GameResult<(usize, String)>;
+
+	fn handle(&mut self, msg: HttpJoin, ctx: &mut Context<Self>) -> Self::Result {
+		let init = ClientMessage::Init {
+			username: msg.username,
+			password: msg.password,
+			protocol_version: PROTOCOL_VERSION,
+			last_seq: None,
+			seat_token: msg.seat_token,
+		};
+		match self.handle_client_msg(None, init, msg.identity, None, Encoding::Json, ctx)? {
+			SeatChange::Seated(player_id) => Ok((player_id, self.players[player_id].token.clone())),
+			_ => Err(GameError::Internal),
+		}
+	}
+}
+
+/// A client message from the seat that goes with `token`
+pub struct HttpAction {
+	pub token: String,
+	pub message: ClientMessage,
+}
+impl Message for HttpAction {
+	type Result = GameResult<()>;
+}
+
+impl Handler<HttpAction> for Game {
+	type Result = GameResult<()>;
+
+	fn handle(&mut self, msg: HttpAction, ctx: &mut Context<Self>) -> Self::Result {
+		let player_id = self.seat_for_token(&msg.token)?;
+		let web_socket = self.players[player_id].web_socket.clone();
+		self.handle_client_msg(Some(player_id), msg.message, None, web_socket, Encoding::Json, ctx)?;
+		Ok(())
+	}
+}
+
+/// The table as seen from the seat, serialized along with the `seq` of the last message sent to it
+pub struct HttpView {
+	pub token: String,
+}
+impl Message for HttpView {
+	type Result = GameResult<String>;
+}
+
+impl Handler<HttpView> for Game {
+	type Result = GameResult<String>;
+
+	fn handle(&mut self, msg: HttpView, _ctx: &mut Context<Self>) -> Self::Result {
+		let player_id = self.seat_for_token(&msg.token)?;
+		Ok(serde_json::to_string(&serde_json::json!({
+			"seq": self.players[player_id].outbox.borrow().last_seq(),
+			"view": self.view(player_id),
+		}))
+		.unwrap())
+	}
+}
+
+/// Messages sent to the seat after `after`, or its latest one if unset
+pub struct HttpPoll {
+	pub token: String,
+	pub after: Option<u64>,
+}
+impl Message for HttpPoll {
+	type Result = GameResult<HttpPollResult>;
+}
+
+pub enum HttpPollResult {
+	Messages(Vec<String>),
+	/// Nothing new after the `after` that was polled yet: notified once there is
+	Wait {
+		after: u64,
+		last_seq: watch::Receiver<u64>,
+	},
+	/// Not all of them are kept anymore, the view should be fetched again
+	Gone,
+}
+
+impl Handler<HttpPoll> for Game {
+	type Result = GameResult<HttpPollResult>;
+
+	fn handle(&mut self, msg: HttpPoll, _ctx: &mut Context<Self>) -> Self::Result {
+		let player_id = self.seat_for_token(&msg.token)?;
+		let outbox = self.players[player_id].outbox.borrow();
+		let after = msg.after.unwrap_or_else(|| outbox.last_seq());
+		if outbox.encoding() != Encoding::Json {
+			return Ok(HttpPollResult::Gone);
+		}
+		let messages: Vec<String> = match outbox.since(after) {
+			Some(messages) => messages
+				.filter_map(|encoded| match encoded {
+					EncodedMessage::Text(json) => Some(json.clone()),
+					EncodedMessage::Binary(_) => None,
+				})
+				.collect(),
+			None => return Ok(HttpPollResult::Gone),
+		};
+		Ok(match messages.is_empty() {
+			true => HttpPollResult::Wait {
+				after,
+				last_seq: outbox.subscribe(),
+			},
+			false => HttpPollResult::Messages(messages),
+		})
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Disconnected {
+	pub player_id: usize,
+	pub web_socket: Addr<WebSocket>,
+}
+
+impl Handler<Disconnected> for Game {
+	type Result = ();
+
+	fn handle(&mut self, msg: Disconnected, ctx: &mut Context<Self>) {
+		// The player may have already reconnected through another socket
+		let player = &self.players[msg.player_id];
+		if player.web_socket.as_ref() != Some(&msg.web_socket) || player.control != Control::Human {
+			return;
+		}
+		debug!("Player {} disconnected", msg.player_id);
+		self.set_control(msg.player_id, Control::Disconnected);
+		if let Some(grace) = self.settings.disconnect_grace_secs.map(Duration::from_secs) {
+			let player_id = msg.player_id;
+			self.players[player_id].grace_timer = Some(ctx.run_later(grace, move |game, ctx| {
+				game.players[player_id].grace_timer = None;
+				if game.players[player_id].control == Control::Disconnected {
+					debug!("Bot takes over seat {}", player_id);
+					game.set_control(player_id, Control::Bot);
+					game.update_timers(ctx);
+				}
+			}));
+		}
+	}
+}
+
+impl Game {
+	fn handle_client_msg(
+		&mut self,
+		player_id: Option<usize>,
+		message: ClientMessage,
+		identity: Option<Identity>,
+		web_socket: Option<Addr<WebSocket>>,
+		encoding: Encoding,
+		ctx: &mut Context<Self>,
+	) -> GameResult<SeatChange> {
+		let res = self.handle_msg(player_id, message, identity, web_socket, encoding);
+		if let Ok(SeatChange::Seated(player_id)) = res {
+			if let Some(handle) = self.players[player_id].grace_timer.take() {
+				ctx.cancel_future(handle);
+			}
+		}
+		self.update_timers(ctx);
+		res
+	}
+
+	fn seat_for_token(&self, token: &str) -> GameResult<usize> {
+		self.players
+			.iter()
+			.position(|p| p.control != Control::Vacant && p.token == token)
+			.ok_or(GameError::InvalidSeatToken)
+	}
+
+	fn update_timers(&mut self, ctx: &mut Context<Self>) {
+		self.update_turn_timer(ctx);
+		self.schedule_bot_turn(ctx);
+		self.schedule_undo_expiry(ctx);
+		self.notify_turn();
+	}
+
+	/// Lets the notifier know about seats that now have to act, once per step
+	fn notify_turn(&mut self) {
+		let (notifier, turn) = match (&self.notifier, self.current_turn()) {
+			(Some(notifier), Some(turn)) => (notifier, turn),
+			_ => return,
+		};
+		let step = self.turn_step();
+		if self.notified_step == Some(step) {
+			return;
+		}
+		self.notified_step = Some(step);
+		for player_id in self.turn_player_ids(turn) {
+			let player = &self.players[player_id];
+			if player.control.is_bot() {
+				continue;
+			}
+			notifier.notify(TurnNotification {
+				table_id: self.table_id.clone(),
+				player_id,
+				username: player.username.clone(),
+				account: player.account.clone(),
+				turn,
+				actions: self
+					.legal_actions(player_id)
+					.into_iter()
+					.map(NotifiedAction::from)
+					.collect(),
+				deadline: self.turn_deadline.map(|d| d.deadline),
+			});
+		}
+	}
+
+	fn schedule_undo_expiry(&mut self, ctx: &mut Context<Self>) {
+		if let GameState::Running(RunningGame { undo: Some(undo), .. }) = &mut self.game_state {
+			if !undo.expiry_scheduled {
+				undo.expiry_scheduled = true;
+				let window = Duration::from_secs(self.settings.undo_window_secs);
+				let expires_at = undo.expires_at;
+				ctx.run_later(window, move |game, ctx| {
+					game.expire_undo(expires_at);
+					game.update_timers(ctx);
+				});
+			}
+		}
+	}
+
+	fn schedule_bot_turn(&mut self, ctx: &mut Context<Self>) {
+		let turn = match self.current_turn() {
+			Some(turn) => turn,
+			None => return,
+		};
+		let step = self.turn_step();
+		if self.bot_step == Some(step)
+			|| !self
+				.turn_player_ids(turn)
+				.into_iter()
+				.any(|id| self.players[id].control.is_bot())
+			// Bots don't play on their own
+			|| !self.players.iter().any(|p| !p.control.is_bot())
+		{
+			return;
+		}
+		self.bot_step = Some(step);
+		ctx.run_later(BOT_DELAY, move |game, ctx| {
+			game.bot_step = None;
+			if game.current_turn() == Some(turn) && game.turn_step() == step {
+				if let Err(err) = game.play_bot_turn(turn) {
+					warn!("Bot could not play turn {:?}: {}", turn, err);
+				}
+			}
+			game.update_timers(ctx);
+		});
+	}
 
-	fn handle(&mut self, msg: ClientGameMessage, _ctx: &mut Context<Self>) -> Self::Result {
-		self.handle_msg(msg.player_id, msg.message, msg.web_socket)
+	/// (Re)starts the clock if the game is now waiting for a different action
+	fn update_turn_timer(&mut self, ctx: &mut Context<Self>) {
+		let turn = self.current_turn();
+		let step = self.turn_step();
+		let same_turn = self
+			.turn_deadline
+			.filter(|d| Some((d.turn, d.step)) == turn.map(|t| (t, step)));
+		if same_turn.is_some() && self.turn_timer.is_some() {
+			return;
+		}
+		if let Some(handle) = self.turn_timer.take() {
+			ctx.cancel_future(handle);
+		}
+		self.turn_deadline = None;
+		// Set without a timer on resume, with what was left when the game was paused
+		let time_left = same_turn.map(|d| {
+			let left_ms = d.deadline - chrono::Utc::now().timestamp_millis();
+			Duration::from_millis(left_ms.max(0) as u64)
+		});
+		if let Some((turn, timeout)) = turn.and_then(|t| Some((t, time_left.or(self.settings.turn_timeout(t))?))) {
+			let deadline = TurnDeadline {
+				turn,
+				deadline: (chrono::Utc::now() + chrono::Duration::from_std(timeout).unwrap()).timestamp_millis(),
+				step,
+			};
+			self.turn_deadline = Some(deadline);
+			self.turn_timer = Some(ctx.run_later(timeout, move |game, ctx| {
+				game.turn_timer = None;
+				game.turn_deadline = None;
+				if game.current_turn() == Some(turn) && game.turn_step() == step {
+					debug!("Turn {:?} timed out", turn);
+					if let Err(err) = game.play_timed_out_turn(turn) {
+						warn!("Could not play timed out turn {:?}: {}", turn, err);
+					}
+				}
+				game.update_timers(ctx);
+			}));
+			self.send_all(ServerMessage::TurnDeadline(deadline));
+		}
 	}
 }
@@ -1,7 +1,15 @@
+pub mod accounts;
 pub mod game;
+pub mod protocol;
+pub mod seat;
+pub mod tables;
+pub mod tournament;
 pub mod websocket;
 
-use crate::prelude::*;
+use {
+	self::tables::Tables,
+	crate::{accounts::Accounts, notifier::TurnNotifier, prelude::*, tournament::Tournaments},
+};
 
 use {
 	actix::prelude::*,
@@ -12,20 +20,42 @@ use {
 		middleware, web, App, HttpResponse, HttpServer,
 	},
 	futures::future::Either,
+	std::sync::Arc,
 };
 
-pub async fn start(port: u16) {
-	let game_addr = Game::new().start();
+pub async fn start(
+	port: u16,
+	settings: Settings,
+	accounts: Accounts,
+	notifier: Option<Arc<dyn TurnNotifier>>,
+	behind_proxy: accounts::BehindProxy,
+) {
+	let accounts_addr = accounts.start();
+	let tables_addr = Tables::default().start();
+	let tournaments_addr = Tournaments::new(
+		settings.clone(),
+		accounts_addr.clone(),
+		tables_addr.clone(),
+		notifier.clone(),
+	)
+	.start();
+	let mut game = Game::new(settings, accounts_addr.clone());
+	game.notifier = notifier;
+	let game_addr = game.start();
 
 	let webserver = HttpServer::new(move || {
 		App::new()
 			.app_data(web::Data::new(game_addr.clone()))
+			.app_data(web::Data::new(accounts_addr.clone()))
+			.app_data(web::Data::new(tables_addr.clone()))
+			.app_data(web::Data::new(tournaments_addr.clone()))
+			.app_data(web::Data::new(behind_proxy))
 			.wrap(middleware::Logger::default())
 			.wrap(middleware::Compress::default())
 			.wrap_fn(|req, srv| {
 				// Enforce HTTPS if forwarded from http (heroku)
 				let headers = req.headers();
-				if headers.get("X-Forwarded-Proto").map_or(false, |v| v == "http") {
+				if headers.get("X-Forwarded-Proto").is_some_and(|v| v == "http") {
 					let host_header = headers.get(header::HOST);
 					let host_header_str = host_header.and_then(|h| h.to_str().ok()).unwrap_or("perdu.com");
 					let location = format!("https://{}{}", host_header_str, req.path());
@@ -47,6 +77,30 @@ pub async fn start(port: u16) {
 				}
 			})
 			.route("/ws/", web::get().to(websocket::index))
+			.route("/ws/{table_id}", web::get().to(websocket::table_index))
+			.route("/api/table/join", web::post().to(seat::join))
+			.route("/api/table/view", web::get().to(seat::view))
+			.route("/api/table/actions", web::post().to(seat::action))
+			.route("/api/table/events", web::get().to(seat::events))
+			.route("/api/tables/{table_id}/join", web::post().to(seat::join))
+			.route("/api/tables/{table_id}/view", web::get().to(seat::view))
+			.route("/api/tables/{table_id}/actions", web::post().to(seat::action))
+			.route("/api/tables/{table_id}/events", web::get().to(seat::events))
+			.route("/api/register", web::post().to(accounts::register))
+			.route("/api/login", web::post().to(accounts::login))
+			.route("/api/logout", web::post().to(accounts::logout))
+			.route("/api/me", web::get().to(accounts::me))
+			.route("/api/stats/{username}", web::get().to(accounts::stats))
+			.route("/api/leaderboard", web::get().to(accounts::leaderboard))
+			.route("/api/protocol/schema.json", web::get().to(protocol::schema))
+			.route("/api/protocol/types.ts", web::get().to(protocol::typescript))
+			.route("/api/tournaments", web::post().to(tournament::create))
+			.route("/api/tournaments/{id}", web::get().to(tournament::get))
+			.route("/api/tournaments/{id}/start", web::post().to(tournament::start))
+			.route(
+				"/api/tournaments/{id}/tables/{table}/close",
+				web::post().to(tournament::close_table),
+			)
 			.service(fs::Files::new("/", "./static").index_file("index.html"))
 	})
 	.bind((std::net::Ipv4Addr::UNSPECIFIED, port))
@@ -0,0 +1,12 @@
+use actix_web::HttpResponse;
+
+/// For third-party clients to check their messages against
+pub async fn schema() -> HttpResponse {
+	HttpResponse::Ok().json(crate::protocol::json_schema())
+}
+
+pub async fn typescript() -> HttpResponse {
+	HttpResponse::Ok()
+		.content_type("application/typescript")
+		.body(crate::protocol::typescript())
+}
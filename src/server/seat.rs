@@ -0,0 +1,190 @@
+use {
+	super::{
+		accounts::{authenticate, error_response},
+		game::{HttpAction, HttpJoin, HttpPoll, HttpPollResult, HttpView},
+		tables::{GetTable, Tables},
+	},
+	crate::{accounts::Accounts, prelude::*},
+};
+
+use {
+	actix_web::{
+		http::{header, StatusCode},
+		web, HttpRequest, HttpResponse,
+	},
+	std::time::Duration,
+};
+
+/// How long an events request is held when there is nothing new
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+pub struct JoinForm {
+	username: String,
+	#[serde(default)]
+	password: Option<String>,
+	/// Token the seat was last given, for guests to take it back
+	#[serde(default)]
+	seat_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EventsParams {
+	/// `seq` of the last message received
+	after: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+	#[serde(flatten)]
+	error: &'a GameError,
+	message: String,
+}
+
+fn game_error_response(error: &GameError) -> HttpResponse {
+	let status = match error {
+		GameError::InvalidSeatToken => StatusCode::UNAUTHORIZED,
+		GameError::WrongPassword | GameError::UsernameTaken | GameError::TableReserved => StatusCode::FORBIDDEN,
+		_ => StatusCode::BAD_REQUEST,
+	};
+	HttpResponse::build(status).json(ErrorBody {
+		error,
+		message: error.to_string(),
+	})
+}
+
+/// The main table, or the one named in the path
+async fn table(req: &HttpRequest, game_addr: &Addr<Game>, tables: &Addr<Tables>) -> Result<Addr<Game>, HttpResponse> {
+	let table_id = match req.match_info().get("table_id") {
+		Some(table_id) => table_id.to_owned(),
+		None => return Ok(game_addr.clone()),
+	};
+	match tables.send(GetTable { table_id }).await {
+		Ok(Some(game_addr)) => Ok(game_addr),
+		Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "Unknown table")),
+		Err(_) => Err(HttpResponse::InternalServerError().finish()),
+	}
+}
+
+/// Sent as `Authorization: Bearer <token>`
+fn seat_token(req: &HttpRequest) -> Result<String, HttpResponse> {
+	req.headers()
+		.get(header::AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.strip_prefix("Bearer "))
+		.map(str::to_owned)
+		.ok_or_else(|| game_error_response(&GameError::InvalidSeatToken))
+}
+
+/// Takes a seat just like `Init` does, returning the token to act on its behalf
+pub async fn join(
+	req: HttpRequest,
+	form: web::Json<JoinForm>,
+	game_addr: web::Data<Addr<Game>>,
+	tables: web::Data<Addr<Tables>>,
+	accounts: web::Data<Addr<Accounts>>,
+) -> HttpResponse {
+	let game_addr = match table(&req, &game_addr, &tables).await {
+		Ok(game_addr) => game_addr,
+		Err(response) => return response,
+	};
+	let form = form.into_inner();
+	let join = HttpJoin {
+		username: form.username,
+		password: form.password,
+		seat_token: form.seat_token,
+		identity: authenticate(&req, &accounts).await,
+	};
+	match game_addr.send(join).await {
+		Ok(Ok((player_id, token))) => HttpResponse::Ok().json(serde_json::json!({
+			"player_id": player_id,
+			"token": token,
+		})),
+		Ok(Err(err)) => game_error_response(&err),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+/// `{"seq": ..., "view": TableView}`, events should then be requested after `seq`
+pub async fn view(req: HttpRequest, game_addr: web::Data<Addr<Game>>, tables: web::Data<Addr<Tables>>) -> HttpResponse {
+	let (game_addr, token) = match (table(&req, &game_addr, &tables).await, seat_token(&req)) {
+		(Ok(game_addr), Ok(token)) => (game_addr, token),
+		(Err(response), _) | (_, Err(response)) => return response,
+	};
+	match game_addr.send(HttpView { token }).await {
+		Ok(Ok(json)) => HttpResponse::Ok().content_type("application/json").body(json),
+		Ok(Err(err)) => game_error_response(&err),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+/// Any `ClientMessage` but `Init`, as JSON
+pub async fn action(
+	req: HttpRequest,
+	body: String,
+	game_addr: web::Data<Addr<Game>>,
+	tables: web::Data<Addr<Tables>>,
+) -> HttpResponse {
+	let (game_addr, token) = match (table(&req, &game_addr, &tables).await, seat_token(&req)) {
+		(Ok(game_addr), Ok(token)) => (game_addr, token),
+		(Err(response), _) | (_, Err(response)) => return response,
+	};
+	let message = match serde_json::from_str(&body) {
+		Ok(message) => message,
+		Err(err) => {
+			return game_error_response(&GameError::InvalidMessage {
+				details: err.to_string(),
+			})
+		}
+	};
+	match game_addr.send(HttpAction { token, message }).await {
+		Ok(Ok(())) => HttpResponse::NoContent().finish(),
+		Ok(Err(err)) => game_error_response(&err),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+/// Long polling: `{"messages": [...]}` as they're sent over the websocket, empty if nothing happened for a while
+pub async fn events(
+	req: HttpRequest,
+	params: web::Query<EventsParams>,
+	game_addr: web::Data<Addr<Game>>,
+	tables: web::Data<Addr<Tables>>,
+) -> HttpResponse {
+	let (game_addr, token) = match (table(&req, &game_addr, &tables).await, seat_token(&req)) {
+		(Ok(game_addr), Ok(token)) => (game_addr, token),
+		(Err(response), _) | (_, Err(response)) => return response,
+	};
+	let mut after = params.after;
+	loop {
+		let poll = HttpPoll {
+			token: token.clone(),
+			after,
+		};
+		let messages = match game_addr.send(poll).await {
+			Ok(Ok(HttpPollResult::Messages(messages))) => messages,
+			Ok(Ok(HttpPollResult::Wait {
+				after: polled,
+				mut last_seq,
+			})) => {
+				// Anything pushed since the poll wakes us up, and is then fetched from the same point
+				after = Some(polled);
+				match tokio::time::timeout(POLL_TIMEOUT, last_seq.changed()).await {
+					Ok(Ok(())) => continue,
+					Ok(Err(_)) | Err(_) => Vec::new(),
+				}
+			}
+			Ok(Ok(HttpPollResult::Gone)) => {
+				return error_response(
+					StatusCode::GONE,
+					"Messages were missed, the view should be fetched again",
+				)
+			}
+			Ok(Err(err)) => return game_error_response(&err),
+			Err(_) => return HttpResponse::InternalServerError().finish(),
+		};
+		return HttpResponse::Ok()
+			.content_type("application/json")
+			.body(format!("{{\"messages\":[{}]}}", messages.join(",")));
+	}
+}
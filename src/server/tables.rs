@@ -0,0 +1,42 @@
+use crate::prelude::*;
+
+use {actix::prelude::*, std::collections::HashMap};
+
+/// Tables other than the main one, reached at `/ws/{table_id}`
+#[derive(Default)]
+pub struct Tables {
+	tables: HashMap<String, Addr<Game>>,
+}
+
+impl Actor for Tables {
+	type Context = Context<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct OpenTable {
+	pub table_id: String,
+	pub game: Addr<Game>,
+}
+
+impl Handler<OpenTable> for Tables {
+	type Result = ();
+
+	fn handle(&mut self, msg: OpenTable, _ctx: &mut Context<Self>) {
+		self.tables.insert(msg.table_id, msg.game);
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<Addr<Game>>")]
+pub struct GetTable {
+	pub table_id: String,
+}
+
+impl Handler<GetTable> for Tables {
+	type Result = Option<Addr<Game>>;
+
+	fn handle(&mut self, msg: GetTable, _ctx: &mut Context<Self>) -> Self::Result {
+		self.tables.get(&msg.table_id).cloned()
+	}
+}
@@ -0,0 +1,124 @@
+use {
+	super::accounts::{authenticate, error_response},
+	crate::{
+		accounts::{Accounts, IsRegistered},
+		prelude::*,
+		tournament::*,
+	},
+};
+
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse};
+
+#[derive(Deserialize)]
+pub struct TournamentForm {
+	name: String,
+	pairs: Vec<[String; 2]>,
+	rounds: usize,
+	match_points: usize,
+	/// Deals per match, for duplicate scoring
+	#[serde(default)]
+	duplicate_boards: Option<usize>,
+}
+
+/// Only registered players may organize a tournament
+pub async fn create(
+	req: HttpRequest,
+	form: web::Json<TournamentForm>,
+	accounts: web::Data<Addr<Accounts>>,
+	tournaments: web::Data<Addr<Tournaments>>,
+) -> HttpResponse {
+	let identity = match authenticate(&req, &accounts).await {
+		Some(identity) => identity,
+		None => return error_response(StatusCode::UNAUTHORIZED, "Not logged in"),
+	};
+	let form = form.into_inner();
+	// Seats of registered players are only given to their account
+	let mut pairs = Vec::with_capacity(form.pairs.len());
+	for players in form.pairs {
+		let mut registered = [false; 2];
+		for (registered, username) in registered.iter_mut().zip(players.iter()) {
+			*registered = match accounts
+				.send(IsRegistered {
+					username: username.clone(),
+				})
+				.await
+			{
+				Ok(registered) => registered,
+				Err(_) => return HttpResponse::InternalServerError().finish(),
+			};
+		}
+		pairs.push(Pair { players, registered });
+	}
+	let create = CreateTournament {
+		name: form.name,
+		organizer: identity.username,
+		pairs,
+		rounds_count: form.rounds,
+		match_points: form.match_points,
+		duplicate_boards: form.duplicate_boards,
+	};
+	match tournaments.send(create).await {
+		Ok(Ok(id)) => HttpResponse::Ok().json(serde_json::json!({ "id": id })),
+		Ok(Err(err)) => error_response(StatusCode::BAD_REQUEST, &err.to_string()),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+pub async fn start(
+	req: HttpRequest,
+	tournament_id: web::Path<usize>,
+	accounts: web::Data<Addr<Accounts>>,
+	tournaments: web::Data<Addr<Tournaments>>,
+) -> HttpResponse {
+	let identity = match authenticate(&req, &accounts).await {
+		Some(identity) => identity,
+		None => return error_response(StatusCode::UNAUTHORIZED, "Not logged in"),
+	};
+	let start = StartTournament {
+		tournament_id: tournament_id.into_inner(),
+		organizer: identity.username,
+	};
+	match tournaments.send(start).await {
+		Ok(Ok(())) => HttpResponse::Ok().finish(),
+		Ok(Err(err)) => error_response(StatusCode::BAD_REQUEST, &err.to_string()),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+/// Table given by its index in the current round
+pub async fn close_table(
+	req: HttpRequest,
+	path: web::Path<(usize, usize)>,
+	accounts: web::Data<Addr<Accounts>>,
+	tournaments: web::Data<Addr<Tournaments>>,
+) -> HttpResponse {
+	let identity = match authenticate(&req, &accounts).await {
+		Some(identity) => identity,
+		None => return error_response(StatusCode::UNAUTHORIZED, "Not logged in"),
+	};
+	let (tournament_id, table) = path.into_inner();
+	let close = CloseTable {
+		tournament_id,
+		table,
+		organizer: identity.username,
+	};
+	match tournaments.send(close).await {
+		Ok(Ok(())) => HttpResponse::Ok().finish(),
+		Ok(Err(err)) => error_response(StatusCode::BAD_REQUEST, &err.to_string()),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
+
+/// Rounds with their tables, and standings
+pub async fn get(tournament_id: web::Path<usize>, tournaments: web::Data<Addr<Tournaments>>) -> HttpResponse {
+	match tournaments
+		.send(GetTournament {
+			tournament_id: tournament_id.into_inner(),
+		})
+		.await
+	{
+		Ok(Some(json)) => HttpResponse::Ok().content_type("application/json").body(json),
+		Ok(None) => error_response(StatusCode::NOT_FOUND, "Unknown tournament"),
+		Err(_) => HttpResponse::InternalServerError().finish(),
+	}
+}
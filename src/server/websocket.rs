@@ -1,4 +1,11 @@
-use {super::game::ClientGameMessage, crate::prelude::*};
+use {
+	super::{
+		accounts::authenticate,
+		game::{ClientGameMessage, Disconnected},
+		tables::{GetTable, Tables},
+	},
+	crate::{accounts::Accounts, messages::ClientRequest, prelude::*},
+};
 
 use {
 	actix::prelude::*,
@@ -12,6 +19,9 @@ use {
 pub struct WebSocket {
 	game_addr: Addr<Game>,
 	player_id: Option<usize>,
+	/// Account the connection was authenticated as, from the session cookie
+	identity: Option<Identity>,
+	encoding: Encoding,
 }
 
 impl Actor for WebSocket {
@@ -22,6 +32,15 @@ impl Actor for WebSocket {
 		// (heroku's proxy disconnects idle connections)
 		ctx.run_interval(Duration::from_secs(5), |_act, ctx| ctx.ping(&[]));
 	}
+
+	fn stopped(&mut self, ctx: &mut Self::Context) {
+		if let Some(player_id) = self.player_id {
+			self.game_addr.do_send(Disconnected {
+				player_id,
+				web_socket: ctx.address(),
+			});
+		}
+	}
 }
 
 /// Handler for ws::Message message
@@ -31,58 +50,91 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WebSocket {
 			Ok(msg) => msg,
 			Err(_) => return,
 		};
-		match msg {
-			ws::Message::Ping(msg) => ctx.pong(&msg),
+		let request = match msg {
+			ws::Message::Ping(msg) => return ctx.pong(&msg),
 			ws::Message::Text(text) => {
 				debug!("Got message from {:?}: {}", self.player_id, text);
-				match serde_json::from_str::<ClientMessage>(&text) {
-					Err(deser_err) => ctx.text(
-						ServerMessage::Error {
-							message: &deser_err.to_string(),
-						}
-						.to_json_string(),
-					),
-					Ok(client_message) => {
-						ctx.spawn(
-							self.game_addr
-								.send(ClientGameMessage {
-									message: client_message,
-									player_id: self.player_id,
-									web_socket: ctx.address(),
-								})
-								.into_actor(self)
-								.then(|res, act, ctx| {
-									match res.unwrap() {
-										Ok(Some(player_id)) => act.player_id = Some(player_id),
-										Ok(None) => (),
-										Err(err) => ctx.text(
-											ServerMessage::Error {
-												message: &format!("{:?}", err),
-											}
-											.to_json_string(),
-										),
+				ClientRequest::from_json(&text)
+			}
+			ws::Message::Binary(bytes) if self.encoding == Encoding::MsgPack => {
+				debug!("Got {} bytes from {:?}", bytes.len(), self.player_id);
+				ClientRequest::from_msgpack(&bytes)
+			}
+			ws::Message::Binary(_) => return ctx.text("Not expecting binary"),
+			ws::Message::Continuation(_) => return ctx.text("Not expecting continuation"),
+			ws::Message::Close(_) => return ctx.stop(),
+			ws::Message::Nop | ws::Message::Pong(_) => return,
+		};
+		match request {
+			Err((request_id, details)) => self.reply(
+				ctx,
+				&ServerMessage::error(&GameError::InvalidMessage { details }, request_id),
+			),
+			Ok(ClientRequest { request_id, message }) => {
+				ctx.spawn(
+					self.game_addr
+						.send(ClientGameMessage {
+							message,
+							player_id: self.player_id,
+							identity: self.identity.clone(),
+							web_socket: ctx.address(),
+							encoding: self.encoding,
+						})
+						.into_actor(self)
+						.then(move |res, act, ctx| {
+							match res.unwrap() {
+								Ok(seat_change) => {
+									match seat_change {
+										SeatChange::Seated(player_id) => act.player_id = Some(player_id),
+										SeatChange::Left => act.player_id = None,
+										SeatChange::Unchanged => (),
+									}
+									if let Some(request_id) = request_id {
+										act.reply(ctx, &ServerMessage::Ack { request_id });
 									}
-									future::ready(())
-								}),
-						);
-					}
-				}
+								}
+								Err(err) => act.reply(ctx, &ServerMessage::error(&err, request_id)),
+							}
+							future::ready(())
+						}),
+				);
 			}
-			ws::Message::Continuation(_) => ctx.text("Not expecting continuation"),
-			ws::Message::Binary(_) => ctx.text("Not expecting binary"),
-			ws::Message::Close(_) => ctx.stop(),
-			ws::Message::Nop | ws::Message::Pong(_) => (),
 		}
 	}
 }
 
+impl WebSocket {
+	/// Messages that aren't from the game, so not numbered
+	fn reply(&self, ctx: &mut ws::WebsocketContext<Self>, msg: &ServerMessage) {
+		send_encoded(ctx, self.encoding.encode(msg));
+	}
+}
+
+fn send_encoded(ctx: &mut ws::WebsocketContext<WebSocket>, encoded: EncodedMessage) {
+	match encoded {
+		EncodedMessage::Text(text) => ctx.text(text),
+		EncodedMessage::Binary(bytes) => ctx.binary(bytes),
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct EncodedServerMessage(pub EncodedMessage);
+impl Handler<EncodedServerMessage> for WebSocket {
+	type Result = ();
+	fn handle(&mut self, msg: EncodedServerMessage, ctx: &mut Self::Context) {
+		send_encoded(ctx, msg.0)
+	}
+}
+
+/// Sent by the game when the player got moved to another seat
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct JsonifiedServerMessage(pub String);
-impl<'a> Handler<JsonifiedServerMessage> for WebSocket {
+pub struct SetPlayerId(pub Option<usize>);
+impl Handler<SetPlayerId> for WebSocket {
 	type Result = ();
-	fn handle(&mut self, msg: JsonifiedServerMessage, ctx: &mut Self::Context) {
-		ctx.text(msg.0)
+	fn handle(&mut self, msg: SetPlayerId, _ctx: &mut Self::Context) {
+		self.player_id = msg.0
 	}
 }
 
@@ -90,11 +142,48 @@ pub async fn index(
 	req: HttpRequest,
 	stream: web::Payload,
 	game_addr: web::Data<Addr<Game>>,
+	accounts: web::Data<Addr<Accounts>>,
+) -> Result<HttpResponse, Error> {
+	start(req, stream, game_addr.get_ref().clone(), &accounts).await
+}
+
+pub async fn table_index(
+	req: HttpRequest,
+	stream: web::Payload,
+	table_id: web::Path<String>,
+	tables: web::Data<Addr<Tables>>,
+	accounts: web::Data<Addr<Accounts>>,
+) -> Result<HttpResponse, Error> {
+	let game_addr = tables
+		.send(GetTable {
+			table_id: table_id.into_inner(),
+		})
+		.await
+		.map_err(actix_web::error::ErrorInternalServerError)?
+		.ok_or_else(|| actix_web::error::ErrorNotFound("Unknown table"))?;
+	start(req, stream, game_addr, &accounts).await
+}
+
+#[derive(Deserialize)]
+struct ConnectionParams {
+	#[serde(default)]
+	encoding: Encoding,
+}
+
+async fn start(
+	req: HttpRequest,
+	stream: web::Payload,
+	game_addr: Addr<Game>,
+	accounts: &Addr<Accounts>,
 ) -> Result<HttpResponse, Error> {
+	let ConnectionParams { encoding } = web::Query::<ConnectionParams>::from_query(req.query_string())?.into_inner();
+	let identity = authenticate(&req, accounts).await;
 	ws::start(
 		WebSocket {
-			game_addr: game_addr.get_ref().clone(),
+			game_addr,
 			player_id: None,
+			identity,
+			encoding,
 		},
 		&req,
 		stream,
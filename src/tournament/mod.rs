@@ -0,0 +1,360 @@
+pub mod swiss;
+
+use {
+	self::swiss::Standing,
+	crate::{
+		accounts::Accounts,
+		notifier::TurnNotifier,
+		prelude::*,
+		server::tables::{OpenTable, Tables},
+	},
+};
+
+use {actix::prelude::*, std::sync::Arc};
+
+/// Tournaments being run on this server, each of their matches played at a table of its own
+pub struct Tournaments {
+	tournaments: Vec<Tournament>,
+	/// Rules of the tournament tables, on top of which the match points are set
+	settings: Settings,
+	accounts: Addr<Accounts>,
+	tables: Addr<Tables>,
+	notifier: Option<Arc<dyn TurnNotifier>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tournament {
+	pub id: usize,
+	pub name: String,
+	/// Account which created the tournament, the only one allowed to start it
+	pub organizer: String,
+	pub match_points: usize,
+	/// Deals per match if all tables of a round play the same deals, ranking pairs by matchpoints
+	pub duplicate_boards: Option<usize>,
+	pub rounds_count: usize,
+	pub pairs: Vec<Pair>,
+	pub rounds: Vec<Round>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Pair {
+	/// Account usernames, or names of the players if they aren't registered
+	pub players: [String; 2],
+	/// Which of the players have an account, the others play as guests
+	#[serde(default)]
+	pub registered: [bool; 2],
+}
+
+impl Pair {
+	fn reservations(&self) -> [SeatReservation; 2] {
+		[0, 1].map(|i| match self.registered[i] {
+			true => SeatReservation::Account(self.players[i].clone()),
+			false => SeatReservation::Guest(self.players[i].clone()),
+		})
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct Round {
+	/// Deals of duplicate rounds
+	#[serde(skip)]
+	pub seed: u64,
+	pub tables: Vec<TableMatch>,
+	/// Pair sitting this round out
+	pub bye: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableMatch {
+	pub table_id: String,
+	pub pairs: [usize; 2],
+	/// Final points of each pair, once the match is over
+	pub points: Option<[usize; 2]>,
+	/// Whether the first pair holds the East/West hands of the duplicate deals
+	pub swapped: bool,
+	/// Points of the North/South and East/West hands on each duplicate deal
+	pub boards: Option<Vec<[usize; 2]>>,
+	/// Whether the organizer ended the match before it was over, as a loss for both pairs
+	pub closed: bool,
+}
+
+/// Lets a table report its result to the tournament it belongs to
+#[derive(Debug, Clone)]
+pub struct TournamentTable {
+	pub tournaments: Addr<Tournaments>,
+	pub tournament_id: usize,
+	pub round: usize,
+	pub table: usize,
+	/// No new match is started at the table once it has been played
+	pub finished: bool,
+}
+
+impl Tournaments {
+	pub fn new(
+		settings: Settings,
+		accounts: Addr<Accounts>,
+		tables: Addr<Tables>,
+		notifier: Option<Arc<dyn TurnNotifier>>,
+	) -> Self {
+		Self {
+			tournaments: Vec::new(),
+			settings,
+			accounts,
+			tables,
+			notifier,
+		}
+	}
+
+	fn get_mut(&mut self, tournament_id: usize) -> crate::Result<&mut Tournament> {
+		self.tournaments
+			.get_mut(tournament_id)
+			.ok_or_else(|| err_msg("Unknown tournament"))
+	}
+
+	/// Opens a table for each match of the next round
+	fn start_round(&mut self, tournament_id: usize, ctx: &mut Context<Self>) {
+		let tournament = &mut self.tournaments[tournament_id];
+		let (pairings, bye) = tournament.next_pairings();
+		let round = tournament.rounds.len();
+		let seed = rand::random();
+		let mut tables = Vec::with_capacity(pairings.len());
+		for (table, pairs) in pairings.into_iter().enumerate() {
+			let table_id = format!("{}-{}-{}", tournament_id, round + 1, table + 1);
+			let [[a0, a1], [b0, b1]] = pairs.map(|p| tournament.pairs[p].reservations());
+			let mut game = Game::new(
+				Settings {
+					match_points: Some(tournament.match_points),
+					..self.settings.clone()
+				},
+				self.accounts.clone(),
+			);
+			// Half the tables play the cards the other half's opponents hold
+			let swapped = table % 2 == 1;
+			game.duplicate = tournament
+				.duplicate_boards
+				.map(|boards| Duplicate::new(seed, swapped, boards));
+			game.notifier = self.notifier.clone();
+			game.table_id = Some(table_id.clone());
+			// Partners sit in front of each other
+			game.seat_reservations = Some([a0, b0, a1, b1]);
+			game.tournament_table = Some(TournamentTable {
+				tournaments: ctx.address(),
+				tournament_id,
+				round,
+				table,
+				finished: false,
+			});
+			self.tables.do_send(OpenTable {
+				table_id: table_id.clone(),
+				game: game.start(),
+			});
+			tables.push(TableMatch {
+				table_id,
+				pairs,
+				points: None,
+				swapped,
+				boards: None,
+				closed: false,
+			});
+		}
+		info!("Tournament {} round {} started", tournament_id, round + 1);
+		tournament.rounds.push(Round { seed, tables, bye });
+	}
+
+	/// Starts the next round once every match of the current one is over
+	fn table_over(&mut self, tournament_id: usize, round: usize, ctx: &mut Context<Self>) {
+		let tournament = &self.tournaments[tournament_id];
+		if round + 1 == tournament.rounds.len() && tournament.round_finished() {
+			if tournament.finished() {
+				info!("Tournament {} is over", tournament_id);
+			} else {
+				self.start_round(tournament_id, ctx);
+			}
+		}
+	}
+}
+
+impl Tournament {
+	pub fn started(&self) -> bool {
+		!self.rounds.is_empty()
+	}
+
+	pub fn finished(&self) -> bool {
+		self.rounds.len() == self.rounds_count && self.round_finished()
+	}
+
+	fn round_finished(&self) -> bool {
+		self.rounds
+			.last()
+			.is_some_and(|r| r.tables.iter().all(|t| t.points.is_some()))
+	}
+}
+
+impl Actor for Tournaments {
+	type Context = Context<Self>;
+}
+
+#[derive(Message)]
+#[rtype(result = "crate::Result<usize>")]
+pub struct CreateTournament {
+	pub name: String,
+	pub organizer: String,
+	pub pairs: Vec<Pair>,
+	pub rounds_count: usize,
+	pub match_points: usize,
+	pub duplicate_boards: Option<usize>,
+}
+
+impl Handler<CreateTournament> for Tournaments {
+	type Result = crate::Result<usize>;
+
+	fn handle(&mut self, msg: CreateTournament, _ctx: &mut Context<Self>) -> Self::Result {
+		if msg.duplicate_boards == Some(0) {
+			return Err(err_msg("Duplicate matches need at least one deal"));
+		}
+		if msg.pairs.len() < 2 {
+			return Err(err_msg("At least two pairs are needed"));
+		}
+		if msg.rounds_count == 0 || msg.rounds_count >= msg.pairs.len() + msg.pairs.len() % 2 {
+			return Err(err_msg("Invalid number of rounds for that many pairs"));
+		}
+		let mut players: Vec<&String> = msg.pairs.iter().flat_map(|p| p.players.iter()).collect();
+		players.sort_unstable();
+		players.dedup();
+		if players.len() != msg.pairs.len() * 2 {
+			return Err(err_msg("A player can only be registered once"));
+		}
+		let id = self.tournaments.len();
+		self.tournaments.push(Tournament {
+			id,
+			name: msg.name,
+			organizer: msg.organizer,
+			match_points: msg.match_points,
+			duplicate_boards: msg.duplicate_boards,
+			rounds_count: msg.rounds_count,
+			pairs: msg.pairs,
+			rounds: Vec::new(),
+		});
+		Ok(id)
+	}
+}
+
+#[derive(Message)]
+#[rtype(result = "crate::Result<()>")]
+pub struct StartTournament {
+	pub tournament_id: usize,
+	pub organizer: String,
+}
+
+impl Handler<StartTournament> for Tournaments {
+	type Result = crate::Result<()>;
+
+	fn handle(&mut self, msg: StartTournament, ctx: &mut Context<Self>) -> Self::Result {
+		let tournament = self.get_mut(msg.tournament_id)?;
+		if tournament.organizer != msg.organizer {
+			return Err(err_msg("Only the organizer can start the tournament"));
+		}
+		if tournament.started() {
+			return Err(err_msg("Tournament already started"));
+		}
+		self.start_round(msg.tournament_id, ctx);
+		Ok(())
+	}
+}
+
+/// Sent by a tournament table when its match is over
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct TableResult {
+	pub tournament_id: usize,
+	pub round: usize,
+	pub table: usize,
+	/// Indexed by seat team
+	pub points: [usize; 2],
+	pub boards: Option<Vec<[usize; 2]>>,
+}
+
+impl Handler<TableResult> for Tournaments {
+	type Result = ();
+
+	fn handle(&mut self, msg: TableResult, ctx: &mut Context<Self>) {
+		let tournament = match self.tournaments.get_mut(msg.tournament_id) {
+			Some(tournament) => tournament,
+			None => return,
+		};
+		let table = &mut tournament.rounds[msg.round].tables[msg.table];
+		if table.points.is_some() {
+			return;
+		}
+		// First pair is seated on the first team's seats
+		table.points = Some(msg.points);
+		table.boards = msg.boards;
+		self.table_over(msg.tournament_id, msg.round, ctx);
+	}
+}
+
+/// Ends a match of the current round which can't be completed, such as an abandoned one, as a loss for both pairs.
+/// Whatever the table reports afterwards is ignored.
+#[derive(Message)]
+#[rtype(result = "crate::Result<()>")]
+pub struct CloseTable {
+	pub tournament_id: usize,
+	pub table: usize,
+	pub organizer: String,
+}
+
+impl Handler<CloseTable> for Tournaments {
+	type Result = crate::Result<()>;
+
+	fn handle(&mut self, msg: CloseTable, ctx: &mut Context<Self>) -> Self::Result {
+		let tournament = self.get_mut(msg.tournament_id)?;
+		if tournament.organizer != msg.organizer {
+			return Err(err_msg("Only the organizer can close a table"));
+		}
+		let round = match tournament.rounds.len() {
+			0 => return Err(err_msg("Tournament not started")),
+			rounds => rounds - 1,
+		};
+		let table = tournament.rounds[round]
+			.tables
+			.get_mut(msg.table)
+			.ok_or_else(|| err_msg("Unknown table"))?;
+		if table.points.is_some() {
+			return Err(err_msg("Match already over"));
+		}
+		table.points = Some([0, 0]);
+		table.closed = true;
+		info!("Tournament {} table {} closed", msg.tournament_id, table.table_id);
+		self.table_over(msg.tournament_id, round, ctx);
+		Ok(())
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct TournamentView<'a> {
+	#[serde(flatten)]
+	pub tournament: &'a Tournament,
+	pub finished: bool,
+	pub standings: Vec<Standing>,
+}
+
+/// Serialized by the actor, as the tournament can't leave it
+#[derive(Message)]
+#[rtype(result = "Option<String>")]
+pub struct GetTournament {
+	pub tournament_id: usize,
+}
+
+impl Handler<GetTournament> for Tournaments {
+	type Result = Option<String>;
+
+	fn handle(&mut self, msg: GetTournament, _ctx: &mut Context<Self>) -> Self::Result {
+		let tournament = self.tournaments.get(msg.tournament_id)?;
+		let view = TournamentView {
+			tournament,
+			finished: tournament.finished(),
+			standings: tournament.standings(),
+		};
+		Some(serde_json::to_string(&view).expect("Tournaments should always be serializable"))
+	}
+}
@@ -0,0 +1,291 @@
+use {super::*, crate::game::duplicate};
+
+/// Ranking of a pair, best first once sorted
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing {
+	pub pair_id: usize,
+	pub players: [String; 2],
+	pub wins: usize,
+	/// Sum of the wins of the pairs met, first tie-break
+	pub buchholz: usize,
+	pub points_for: usize,
+	pub points_against: usize,
+	/// Sum of the matchpoint percentages of duplicate rounds
+	pub matchpoints: f64,
+}
+
+impl Standing {
+	fn points_difference(&self) -> i64 {
+		self.points_for as i64 - self.points_against as i64
+	}
+}
+
+impl Tournament {
+	/// Pairs each pair played against, in round order
+	fn opponents(&self, pair_id: usize) -> Vec<usize> {
+		self.rounds
+			.iter()
+			.flat_map(|r| r.tables.iter())
+			.filter_map(|t| match t.pairs {
+				[a, b] if a == pair_id => Some(b),
+				[a, b] if b == pair_id => Some(a),
+				_ => None,
+			})
+			.collect()
+	}
+
+	pub fn standings(&self) -> Vec<Standing> {
+		let mut standings: Vec<Standing> = self
+			.pairs
+			.iter()
+			.enumerate()
+			.map(|(pair_id, pair)| Standing {
+				pair_id,
+				players: pair.players.clone(),
+				wins: 0,
+				buchholz: 0,
+				points_for: 0,
+				points_against: 0,
+				matchpoints: 0.,
+			})
+			.collect();
+		for round in self.rounds.iter() {
+			// Compared once every table played the deals, closed tables scoring no matchpoints
+			if round.tables.iter().all(|t| t.boards.is_some() || t.closed) {
+				let played: Vec<&TableMatch> = round.tables.iter().filter(|t| !t.closed).collect();
+				let results: Vec<Vec<[usize; 2]>> = played.iter().filter_map(|t| t.boards.clone()).collect();
+				for (table, north_south) in played.into_iter().zip(duplicate::matchpoints(&results)) {
+					let [first, second] = match table.swapped {
+						false => [north_south, 100. - north_south],
+						true => [100. - north_south, north_south],
+					};
+					standings[table.pairs[0]].matchpoints += first;
+					standings[table.pairs[1]].matchpoints += second;
+				}
+				// An average round rather than the worst one
+				if let Some(bye) = round.bye {
+					standings[bye].matchpoints += 50.;
+				}
+			}
+			// A bye counts as a win
+			if let Some(bye) = round.bye {
+				standings[bye].wins += 1;
+			}
+			for table in round.tables.iter() {
+				if let Some(points) = table.points {
+					for (side, &pair_id) in table.pairs.iter().enumerate() {
+						let standing = &mut standings[pair_id];
+						standing.points_for += points[side];
+						standing.points_against += points[1 - side];
+						standing.wins += (points[side] > points[1 - side]) as usize;
+					}
+				}
+			}
+		}
+		let wins: Vec<usize> = standings.iter().map(|s| s.wins).collect();
+		for standing in standings.iter_mut() {
+			standing.buchholz = self.opponents(standing.pair_id).into_iter().map(|o| wins[o]).sum();
+		}
+		standings.sort_by(|a, b| match self.duplicate_boards {
+			Some(_) => b
+				.matchpoints
+				.total_cmp(&a.matchpoints)
+				.then((b.buchholz, b.points_difference()).cmp(&(a.buchholz, a.points_difference()))),
+			None => (b.wins, b.buchholz, b.points_difference()).cmp(&(a.wins, a.buchholz, a.points_difference())),
+		});
+		standings
+	}
+
+	/// Swiss pairings for the next round: pairs with the same results meet, avoiding rematches when possible.
+	/// With an odd number of pairs, the lowest ranked one which didn't get a bye yet sits out.
+	pub fn next_pairings(&self) -> (Vec<[usize; 2]>, Option<usize>) {
+		let mut ranked: Vec<usize> = self.standings().into_iter().map(|s| s.pair_id).collect();
+		let bye = match ranked.len() % 2 {
+			0 => None,
+			_ => {
+				let pos = ranked
+					.iter()
+					.rposition(|&p| !self.rounds.iter().any(|r| r.bye == Some(p)))
+					.unwrap_or(ranked.len() - 1);
+				Some(ranked.remove(pos))
+			}
+		};
+		let opponents: Vec<Vec<usize>> = self.pair_ids().map(|p| self.opponents(p)).collect();
+		let pairings = pair_up(&ranked, &|a, b| !opponents[a].contains(&b))
+			// Every pairing would be a rematch: fall back to ranking order
+			.unwrap_or_else(|| ranked.chunks(2).map(|c| [c[0], c[1]]).collect());
+		(pairings, bye)
+	}
+
+	fn pair_ids(&self) -> std::ops::Range<usize> {
+		0..self.pairs.len()
+	}
+}
+
+/// Pairs the best ranked remaining pair with the next best one it may play, backtracking if needed
+fn pair_up(ranked: &[usize], allowed: &dyn Fn(usize, usize) -> bool) -> Option<Vec<[usize; 2]>> {
+	let (&first, rest) = match ranked.split_first() {
+		Some(split) => split,
+		None => return Some(Vec::new()),
+	};
+	for (pos, &opponent) in rest.iter().enumerate() {
+		if !allowed(first, opponent) {
+			continue;
+		}
+		let mut remaining = rest.to_vec();
+		remaining.remove(pos);
+		if let Some(mut pairings) = pair_up(&remaining, allowed) {
+			pairings.insert(0, [first, opponent]);
+			return Some(pairings);
+		}
+	}
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tournament(pairs: usize) -> Tournament {
+		Tournament {
+			id: 0,
+			name: "Test".to_owned(),
+			organizer: "organizer".to_owned(),
+			match_points: 1000,
+			duplicate_boards: None,
+			rounds_count: 3,
+			pairs: (0..pairs)
+				.map(|i| Pair {
+					players: [format!("{i}a"), format!("{i}b")],
+					registered: [false; 2],
+				})
+				.collect(),
+			rounds: Vec::new(),
+		}
+	}
+
+	/// Adds a played round, each match given as the pairs and their points
+	fn play_round(tournament: &mut Tournament, matches: &[([usize; 2], [usize; 2])], bye: Option<usize>) {
+		let tables = matches
+			.iter()
+			.map(|&(pairs, points)| TableMatch {
+				table_id: String::new(),
+				pairs,
+				points: Some(points),
+				swapped: false,
+				boards: None,
+				closed: false,
+			})
+			.collect();
+		tournament.rounds.push(Round { seed: 0, tables, bye });
+	}
+
+	fn ranking(tournament: &Tournament) -> Vec<usize> {
+		tournament.standings().into_iter().map(|s| s.pair_id).collect()
+	}
+
+	#[test]
+	fn standings_rank_by_wins_then_buchholz_then_points() {
+		let mut tournament = tournament(4);
+		play_round(&mut tournament, &[([0, 1], [1000, 500]), ([2, 3], [1000, 900])], None);
+		play_round(&mut tournament, &[([0, 2], [1000, 700]), ([1, 3], [400, 1000])], None);
+		let standings = tournament.standings();
+		assert_eq!(ranking(&tournament), [0, 2, 3, 1]);
+		assert_eq!(standings[0].wins, 2);
+		// 2 and 3 both won once, 2 met the stronger opponents
+		assert_eq!((standings[1].wins, standings[1].buchholz), (1, 3));
+		assert_eq!((standings[2].wins, standings[2].buchholz), (1, 1));
+		assert_eq!(standings[3].points_for, 900);
+		assert_eq!(standings[3].points_against, 2000);
+	}
+
+	#[test]
+	fn byes_count_as_wins() {
+		let mut swiss_tournament = tournament(3);
+		play_round(&mut swiss_tournament, &[([0, 1], [1000, 500])], Some(2));
+		let standings = swiss_tournament.standings();
+		assert_eq!(standings.iter().find(|s| s.pair_id == 2).unwrap().wins, 1);
+
+		// And as an average round in duplicate tournaments, ranked by matchpoints
+		let mut duplicate_tournament = tournament(5);
+		duplicate_tournament.duplicate_boards = Some(2);
+		play_round(
+			&mut duplicate_tournament,
+			&[([0, 1], [1000, 500]), ([2, 3], [600, 1000])],
+			Some(4),
+		);
+		for (table, boards) in duplicate_tournament.rounds[0]
+			.tables
+			.iter_mut()
+			.zip([[[160, 0], [0, 90]], [[100, 0], [0, 90]]])
+		{
+			table.boards = Some(boards.to_vec());
+		}
+		let standings = duplicate_tournament.standings();
+		let bye = standings.iter().position(|s| s.pair_id == 4).unwrap();
+		assert_eq!(standings[bye].matchpoints, 50.);
+		assert_eq!(standings[bye].wins, 1);
+		assert!(standings[..bye].iter().all(|s| s.matchpoints > 50.));
+		assert!(standings[bye + 1..].iter().all(|s| s.matchpoints < 50.));
+	}
+
+	#[test]
+	fn closed_tables_are_lost_by_both_pairs() {
+		let mut swiss_tournament = tournament(4);
+		play_round(&mut swiss_tournament, &[([0, 1], [1000, 500]), ([2, 3], [0, 0])], None);
+		swiss_tournament.rounds[0].tables[1].closed = true;
+		let standings = swiss_tournament.standings();
+		assert_eq!(ranking(&swiss_tournament)[0], 0);
+		assert!(standings.iter().filter(|s| s.pair_id != 0).all(|s| s.wins == 0));
+
+		// The other tables of a duplicate round are only compared with each other
+		let mut duplicate_tournament = tournament(6);
+		duplicate_tournament.duplicate_boards = Some(1);
+		play_round(
+			&mut duplicate_tournament,
+			&[([0, 1], [160, 0]), ([2, 3], [0, 160]), ([4, 5], [0, 0])],
+			None,
+		);
+		for (table, boards) in duplicate_tournament.rounds[0].tables[..2]
+			.iter_mut()
+			.zip([[160, 0], [0, 160]])
+		{
+			table.boards = Some(vec![boards]);
+		}
+		duplicate_tournament.rounds[0].tables[2].closed = true;
+		let mut standings = duplicate_tournament.standings();
+		standings.sort_by_key(|s| s.pair_id);
+		let matchpoints: Vec<f64> = standings.iter().map(|s| s.matchpoints).collect();
+		assert_eq!(matchpoints, [100., 0., 0., 100., 0., 0.]);
+		assert!(standings
+			.iter()
+			.all(|s| s.wins == (s.pair_id == 0 || s.pair_id == 3) as usize));
+	}
+
+	#[test]
+	fn winners_meet_without_rematches() {
+		let mut tournament = tournament(4);
+		assert_eq!(tournament.next_pairings(), (vec![[0, 1], [2, 3]], None));
+		play_round(&mut tournament, &[([0, 1], [1000, 500]), ([2, 3], [1000, 900])], None);
+		assert_eq!(tournament.next_pairings(), (vec![[0, 2], [3, 1]], None));
+		play_round(&mut tournament, &[([0, 2], [1000, 700]), ([3, 1], [1000, 400])], None);
+		// 0 already met 1 and 2, leaving 3 for it
+		assert_eq!(tournament.next_pairings(), (vec![[0, 3], [2, 1]], None));
+	}
+
+	#[test]
+	fn byes_go_to_the_lowest_ranked_pair_without_one() {
+		let mut tournament = tournament(3);
+		assert_eq!(tournament.next_pairings(), (vec![[0, 1]], Some(2)));
+		play_round(&mut tournament, &[([0, 1], [500, 1000])], Some(2));
+		// 0 is last but 2 already sat out
+		assert_eq!(tournament.next_pairings(), (vec![[1, 2]], Some(0)));
+	}
+
+	#[test]
+	fn rematches_are_played_when_unavoidable() {
+		let mut tournament = tournament(2);
+		play_round(&mut tournament, &[([0, 1], [1000, 500])], None);
+		assert_eq!(tournament.next_pairings(), (vec![[0, 1]], None));
+	}
+}
@@ -63,6 +63,7 @@ class Game {
 
 	constructor(player_id) {
 		this.player_id = player_id;
+		this.muted = [];
 	}
 
 	loadState(data) {
@@ -71,8 +72,10 @@ class Game {
 		vue.showNames(this.players);
 
 		vue.updateScoreboard(data.points, data.round_points, this.player_id % 2);
+		this.setHost(data.host_id);
+		this.setPause(data.pause);
 
-		const [type, state] = serde.datatype(data.game_state);
+		const [type, state] = serde.datatype(data.phase);
 		if (type == "Lobby") {
 			vue.message("En attente d'autres joueurs...");
 		}
@@ -84,9 +87,8 @@ class Game {
 				const player = this.localPlayerId(pbid.player_id);
 				this.bids[player] = serde.playerBid(pbid);
 			}
-			const [coinche_state, coinche_infos] = serde.datatype(state.coinche_state);
-			if(coinche_state == "Coinche"){
-				const doubler = this.localPlayerId(coinche_infos.player_id);
+			if (state.coincher_id !== null) {
+				const doubler = this.localPlayerId(state.coincher_id);
 				this.bids[doubler] = new Bid("double");
 				this.highestBid.doubleIt();
 				this.turn = (doubler + 1) % 4;
@@ -97,13 +99,14 @@ class Game {
 			vue.displayAllBids(this.bids);
 			this.bidTurn();
 		}
-		if (type == "Running") {
+		if (type == "Playing") {
 			this.phase = 2;
 			vue.hideBidPicker();
 			this.bids = {}
 			let bid = serde.bid(state.bid, state.coinche_state);
-			this.bids[this.localPlayerId(state.team ? 1 : 0)] = bid;
-			this.bids[this.localPlayerId(state.team ? 3 : 2)] = bid;
+			const team = state.bidder_id % 2;
+			this.bids[this.localPlayerId(team)] = bid;
+			this.bids[this.localPlayerId(team + 2)] = bid;
 			this.trumpColor = bid.color;
 			const board = state.board;
 			this.current_trick = board.cards.map(serde.card);
@@ -112,8 +115,22 @@ class Game {
 			console.log(this.turn);
 			vue.displayTrick(this.starting_player, this.current_trick);
 			vue.displayAllBids(this.bids);
-			if(this.cards !== undefined) this.cardTurn();
 		}
+
+		data.players.forEach((player, player_id) => {
+			if (player_id != this.player_id) vue.drawOtherHand(this.localPlayerId(player_id), player.card_count);
+		});
+		this.setCards(data.hand.map(serde.card));
+	}
+
+	setHost(host_id) {
+		this.host_id = host_id;
+		vue.showHost(host_id === null ? null : this.localPlayerId(host_id));
+	}
+
+	setPause(pause) {
+		this.pause = pause;
+		vue.showPause(pause);
 	}
 
 	setCards(cards) {
@@ -206,6 +223,10 @@ class Game {
 		return (player_id + 4 - this.player_id) % 4;
 	}
 
+	serverPlayerId(player) {
+		return (player + this.player_id) % 4;
+	}
+
 	get highestBidPlayer() {
 		let max_v = 0;
 		let max_p = null;
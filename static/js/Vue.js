@@ -26,6 +26,26 @@ function onCardClick(evt) {
 	attemptPlay(card);
 }
 
+function onNameClick(evt) {
+	const elt = $(this);
+	const player = vue.playerOfNameElt(elt);
+	if (elt.hasClass("Disconnected") || elt.hasClass("Bot")) {
+		if (confirm(`Exclure ${elt.text()} de la table ?`)) voteKick(player);
+	}
+	else if (game.host_id !== null && game.localPlayerId(game.host_id) == 0 && player != 0) {
+		const player_id = game.serverPlayerId(player);
+		const silenced = !game.players[player_id].silenced;
+		if (confirm(silenced ? `Interdire à ${elt.text()} d'écrire ?` : `Autoriser ${elt.text()} à écrire ?`)) {
+			silence(player_id, silenced);
+		}
+	}
+}
+
+function onChatAuthorClick(evt) {
+	const player_id = parseInt($(this).attr("data-player-id"));
+	if (game.localPlayerId(player_id) != 0) toggleMute(player_id);
+}
+
 function onBidChange(evt) {
 	let value = $('input:checked', '#bid-value-picker').val();
 	let color = $('input:checked', '#bid-color-picker').val();
@@ -40,10 +60,20 @@ class Vue {
 
 	static sides = ["bottom", "right", "top", "left"];
 	static clockwiseSides = ["bottom", "left", "top", "right"];
+	static reactions = {
+		WellPlayed: "Bien joué",
+		Oops: "Oups",
+		Thanks: "Merci",
+		Hurry: "Vite !",
+		Laugh: "\u{1F602}",
+		Angry: "\u{1F620}",
+		ThumbsUp: "\u{1F44D}",
+	};
 
 	constructor(clockwise) {
 		this.clockwise = clockwise;
 		$("#bid-picker input").change(onBidChange);
+		$(".name").click(onNameClick);
 		this.freezed = false;
 		this.stack = [];
 		this.hideBidPicker();
@@ -84,6 +114,11 @@ class Vue {
 		return $(`#${this.sideOfPlayer(player)}-name`);
 	}
 
+	playerOfNameElt(elt) {
+		const side = elt.attr("id").split("-")[0];
+		return (this.clockwise ? Vue.clockwiseSides : Vue.sides).indexOf(side);
+	}
+
 	beloteOfPlayer(player){
 		return $(`#${this.sideOfPlayer(player)}-belote`);
 	}
@@ -126,6 +161,7 @@ class Vue {
 
 	displayTrick(starting_player, cards) {
 		if (this.freezed) return this.push("displayTrick", starting_player, cards);
+		$("#current-trick").empty();
 		for (let i = 0; i < cards.length; i++) {
 			this.playCard((starting_player + i) % 4, cards[i], null, true);
 		}
@@ -342,6 +378,19 @@ class Vue {
 		if (turn == 0) this.notifyMyTurn();
 	}
 
+	showDeadline(players, deadline) {
+		window.clearInterval(this.deadlineInterval);
+		$(".timer").remove();
+		const timers = players.map(p => $('<span class="timer"></span>').appendTo(this.nameEltOfPlayer(p)));
+		const update = () => {
+			const secs = Math.max(0, Math.ceil((deadline - Date.now()) / 1000));
+			for (const timer of timers) timer.text(` (${secs}s)`);
+			if (secs == 0) window.clearInterval(this.deadlineInterval);
+		};
+		update();
+		this.deadlineInterval = window.setInterval(update, 1000);
+	}
+
 	notifyMyTurn(){
 		$("#turn_sound")[0].play();
 		document.title = "Coinche - A toi de jouer !"
@@ -353,10 +402,65 @@ class Vue {
 
 	showNames(players) {
 		for (const player in players) {
-			this.nameEltOfPlayer(game.localPlayerId(parseInt(player))).text(players[player].username);
+			const local = game.localPlayerId(parseInt(player));
+			this.nameEltOfPlayer(local).text(players[player].username);
+			this.showControl(local, players[player].control);
 		}
 	}
 
+	showControl(player, control) {
+		this.nameEltOfPlayer(player).removeClass("Human Disconnected Bot Vacant").addClass(control);
+		this.nameEltOfPlayer(player).removeAttr("title");
+	}
+
+	showHost(player) {
+		$(".name").removeClass("host");
+		if (player !== null) this.nameEltOfPlayer(player).addClass("host");
+		if (player === 0) $(".host-action").show();
+		else $(".host-action").hide();
+	}
+
+	showPause(pause) {
+		if (pause) {
+			window.clearInterval(this.deadlineInterval);
+			$(".timer").remove();
+			let text = "Pause";
+			if (pause.resume_votes.length) text += ` (${pause.resume_votes.length} prêt(s) à reprendre)`;
+			$("#paused").text(text).show();
+		}
+		else $("#paused").hide();
+		$("#pause").text(pause ? "Reprendre" : "Pause");
+	}
+
+	showChatHistory(messages) {
+		$("#chat-log").empty();
+		for (const message of messages) this.showChatMessage(message);
+	}
+
+	showChatMessage(message) {
+		const time = new Date(message.timestamp).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+		const author = createElt("span", message.username, undefined, { "class": "chat-author", "data-player-id": message.player_id });
+		author.click(onChatAuthorClick);
+		// text() so that messages can't inject html
+		const line = createElt("div").append(`${time} `, author, ": ", $("<span></span>").text(message.text));
+		const log = $("#chat-log");
+		log.append(line);
+		log.scrollTop(log[0].scrollHeight);
+	}
+
+	showReaction(player, reaction) {
+		const name = this.nameEltOfPlayer(player);
+		$(`.reaction.${this.sideOfPlayer(player)}`).remove();
+		const pos = name.position();
+		const bubble = createElt("div", Vue.reactions[reaction], { top: pos.top - 30, left: pos.left }, { "class": `reaction ${this.sideOfPlayer(player)}` });
+		$("#table").append(bubble);
+		bubble.delay(2500).fadeOut(500, function () { $(this).remove(); });
+	}
+
+	showKickVotes(player, nb_votes) {
+		this.nameEltOfPlayer(player).attr("title", `${nb_votes} vote(s) pour exclure`);
+	}
+
 }
 
 function createElt(tag, content, css, attrs){
@@ -1,4 +1,6 @@
 
+const PROTOCOL_VERSION = 2;
+
 function send(type, data) {
 	if (data === undefined) data = null;
 	toBeSent = {}
@@ -36,11 +38,59 @@ function attemptPlay(card) {
 	send("PlayCard", { "Card": { "suit": card.color, "value": card.value } });
 }
 
+function leaveTable() {
+	if (!confirm("Quitter la table ?")) return;
+	send("Leave");
+	leaving = true;
+	window.location = "index.html";
+}
+
+function claimRemainingTricks() {
+	if (confirm("Annoncer que vous faites tous les plis restants ?")) send("Claim");
+}
+
+function togglePause() {
+	send(game.pause ? "Resume" : "Pause");
+}
+
+function restartMatch() {
+	if (confirm("Recommencer la partie ?")) send("Restart");
+}
+
+function sendChat() {
+	const text = $("#chat-input").val().trim();
+	if (text) send("Chat", text);
+	$("#chat-input").val("");
+}
+
+function toggleMute(player_id) {
+	const muted = !game.muted.includes(player_id);
+	const username = game.players[player_id].username;
+	if (confirm(muted ? `Masquer les messages de ${username} ?` : `Afficher les messages de ${username} ?`)) {
+		if (muted) game.muted.push(player_id);
+		else game.muted = game.muted.filter(p => p != player_id);
+		send("Mute", { player_id: player_id, muted: muted });
+	}
+}
+
+function silence(player_id, silenced) {
+	send("Silence", { player_id: player_id, silenced: silenced });
+}
+
+function voteKick(player) {
+	send("VoteKick", { player_id: game.serverPlayerId(player) });
+}
+
 /* ------ handlers ---- */
 
 function onmessage(event) {
 	try {
-		const [type, data] = serde.datatype(JSON.parse(event.data));
+		const message = JSON.parse(event.data);
+		if (message.seq !== undefined) {
+			lastSeq = message.seq;
+			delete message.seq;
+		}
+		const [type, data] = serde.datatype(message);
 		console.log(type, data);
 		if (messageHandlers[type] === undefined) {
 			console.error("unknow message type", data);
@@ -57,20 +107,17 @@ function onmessage(event) {
 }
 
 const messageHandlers = {
-	Game: function (data) {
+	Welcome: function (data) {
+		localStorage.setItem(seatTokenKey, data.seat_token);
+	},
+
+	Table: function (data) {
 		if (game === undefined) {
 			game = new Game(data.player_id);
 		}
-		game.loadState(data.game);
-	},
-
-	Cards: function (data) {
-		let cards = data.cards.map(serde.card);
-		game.setCards(cards);
-	},
-
-	CardCount: function (data) {
-		vue.drawOtherHand(game.localPlayerId(data.player_id), data.count);
+		// Seats may have been reordered by the host
+		game.player_id = data.player_id;
+		game.loadState(data);
 	},
 
 	PlayerBid: function (data) {
@@ -97,6 +144,93 @@ const messageHandlers = {
 		game.trickWon(winner);
 	},
 
+	Control: function (data) {
+		vue.showControl(game.localPlayerId(data.player_id), data.control);
+	},
+
+	UndoRequest: function (data) {
+		if (game.localPlayerId(data.player_id) == 0) return;
+		window.setTimeout(function () {
+			send("AnswerUndo", confirm(`${game.players[data.player_id].username} veut reprendre sa carte. Accepter ?`));
+		}, 0);
+	},
+
+	UndoResult: function (data) {
+		vue.message(data.accepted ? "Carte reprise" : "Reprise refusée");
+	},
+
+	Claim: function (data) {
+		const player = game.localPlayerId(data.player_id);
+		const cards = data.cards.map(serde.card);
+		vue.message(`${game.players[data.player_id].username} fait le reste avec ${cards.join(", ")}`);
+		// Opponents are asked whether they accept the claim
+		if (data.pending && player % 2 == 1) {
+			window.setTimeout(function () {
+				send("AnswerClaim", confirm(`${game.players[data.player_id].username} annonce faire le reste avec :\n${cards.join(", ")}\nAccepter ?`));
+			}, 0);
+		}
+	},
+
+	ClaimResult: function (data) {
+		vue.message(data.accepted ? "Annonce acceptée" : "Annonce refusée");
+	},
+
+	Chat: function (data) {
+		vue.showChatMessage(data);
+	},
+
+	ChatHistory: function (data) {
+		vue.showChatHistory(data.messages);
+	},
+
+	Reaction: function (data) {
+		vue.showReaction(game.localPlayerId(data.player_id), data.reaction);
+	},
+
+	Silenced: function (data) {
+		game.players[data.player_id].silenced = data.silenced;
+		const username = game.players[data.player_id].username;
+		vue.message(data.silenced ? `${username} ne peut plus écrire` : `${username} peut à nouveau écrire`);
+	},
+
+	MatchEnd: function (data) {
+		let result = "Égalité";
+		if (data.winning_team !== null) {
+			const won = game.localPlayerId(data.winning_team ? 1 : 0) % 2 == 0;
+			result = won ? "Partie gagnée" : "Partie perdue";
+		}
+		vue.message(`${result} (${data.points.join(" - ")})`);
+	},
+
+	RedealVotes: function (data) {
+		vue.message(`${data.votes.length} joueur(s) demandent une redistribution`);
+	},
+
+	Misdeal: function (data) {
+		if (data.waived) vue.message(`Main ${data.misdeal} jouée malgré tout après trop de redistributions`);
+		else vue.message(`Redistribution : main ${data.misdeal}`);
+	},
+
+	Host: function (data) {
+		game.setHost(data.player_id);
+	},
+
+	Pause: function (data) {
+		game.setPause(data.pause);
+	},
+
+	KickVotes: function (data) {
+		vue.showKickVotes(game.localPlayerId(data.player_id), data.votes.length);
+	},
+
+	TurnDeadline: function (data) {
+		const [type, turn] = serde.datatype(data.turn);
+		let players;
+		if (type == "SurCoinche") players = [turn.team ? 1 : 0, turn.team ? 3 : 2];
+		else players = [turn.player_id];
+		vue.showDeadline(players.map(p => game.localPlayerId(p)), data.deadline);
+	},
+
 	Error: function (data) {
 		alert(data.message);
 	},
@@ -1,6 +1,13 @@
 var socket;
 var game;
 var vue;
+// Where the seat token of this table is kept, so that coming back to the page, even after closing the browser, gets the seat back
+var seatTokenKey;
+// `seq` of the last message received, so that only the missed ones are sent again after reconnecting
+var lastSeq = null;
+var leaving = false;
+// Wait before connecting again after losing the connection
+const RECONNECT_DELAY_MS = 2000;
 
 window.addEventListener("load", function () {
 
@@ -8,18 +15,15 @@ window.addEventListener("load", function () {
 
 	const url = new URL(location);
 	const user = url.searchParams.get("user");
-	if (!user) {
-		alert("Please enter an username in the url : [...].html?user=<your name>");
-	}
+	const password = url.searchParams.get("password") || null;
+	// Tournament tables have their own address
+	const table = url.searchParams.get("table") || "";
+	seatTokenKey = `seat_token/${table}/${user}`;
 
 	let match = window.location.href.match(/^http(?<secure>s?):\/\/(?<hostname>[^/]*)/);
 	if (match) {
 		let { secure, hostname } = match.groups;
-		socket = new WebSocket(`ws${secure}://${hostname}/ws/`);
-		socket.onopen = function (event) {
-			send("Init", { username: user });
-		}
-		socket.onmessage = onmessage;
+		connect(`ws${secure}://${hostname}/ws/${table}`, user, password);
 	} else {
 		alert("Could not parse url");
 	}
@@ -29,3 +33,26 @@ window.addEventListener("load", function () {
 		$.get("index.html", {"useless": Math.random()});
 	}, 20*60*1000);
 });
+
+function connect(address, user, password) {
+	socket = new WebSocket(address);
+	socket.onopen = function (event) {
+		// Logged in players are identified by their session cookie instead
+		fetch("/api/me").then(function (res) {
+			if (!res.ok && !user) {
+				alert("Please enter an username in the url : [...].html?user=<your name>");
+			}
+			send("Init", {
+				username: user || "",
+				password: password,
+				protocol_version: PROTOCOL_VERSION,
+				last_seq: lastSeq,
+				seat_token: localStorage.getItem(seatTokenKey),
+			});
+		});
+	}
+	socket.onmessage = onmessage;
+	socket.onclose = function (event) {
+		if (!leaving) window.setTimeout(() => connect(address, user, password), RECONNECT_DELAY_MS);
+	}
+}